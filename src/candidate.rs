@@ -0,0 +1,341 @@
+//! Candidate bitmasks maintained incrementally as cells are filled.
+
+use crate::{
+    logic, NoSolution, Sudoku,
+    SudokuNum::{Edited, Original},
+    Unit,
+};
+use alloc::{format, string::String, vec, vec::Vec};
+
+/// A [`Sudoku`] together with the candidates of every cell, kept up to date by constraint propagation as digits are assigned. Returned by [`Sudoku::candidate_grid`].
+///
+/// Candidates are stored as masks with bit `n - 1` set for each candidate `n`. Assigning a digit removes it from the candidates of every peer sharing a row, column, block or extra region, and any peer left with a single candidate is assigned in turn. Other variant constraints are applied when the grid is created but not propagated.
+///
+/// # Examples
+///
+/// ```
+/// use newdoku::Sudoku;
+/// use std::str::FromStr;
+///
+/// let mut grid = Sudoku::from_str(&".".repeat(81)).unwrap().candidate_grid();
+/// grid.assign(0, 0, 5).unwrap();
+/// assert!(!grid.candidates(8, 0).contains(&5));
+/// assert!(grid.candidates(8, 1).contains(&5));
+/// ```
+#[derive(Debug, Clone)]
+pub struct CandidateGrid {
+    sudoku: Sudoku,
+    /// Candidates of each cell, holding only the digit of a filled cell.
+    masks: [u16; 81],
+}
+
+impl CandidateGrid {
+    /// Returns the grid of digits assigned so far.
+    pub fn sudoku(&self) -> &Sudoku {
+        &self.sudoku
+    }
+
+    /// Returns the candidates of the cell at `(x, y)` (0-indexed) as a mask with bit `n - 1` set for each candidate `n`. A filled cell has only its own digit.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `x` or `y` is not within range 0..9.
+    pub fn mask(&self, x: usize, y: usize) -> u16 {
+        assert!(x < 9 && y < 9, "each coordinate must be within range 0..9");
+        self.masks[y * 9 + x]
+    }
+
+    /// Returns the candidates of the cell at `(x, y)` (0-indexed) in ascending order. A filled cell has only its own digit.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `x` or `y` is not within range 0..9.
+    pub fn candidates(&self, x: usize, y: usize) -> Vec<u8> {
+        let mask = self.mask(x, y);
+        (1..10).filter(|num| mask & 1 << (num - 1) != 0).collect()
+    }
+
+    /// Assigns `num` to the cell at `(x, y)` (0-indexed) and propagates the consequences.
+    ///
+    /// Returns [`NoSolution`] if `num` is not a candidate of the cell or propagation leaves a cell without candidates, in which case the grid is left partially updated and should be discarded.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `x` or `y` is not within range 0..9, or `num` is not within range 1..=9.
+    pub fn assign(&mut self, x: usize, y: usize, num: u8) -> Result<(), NoSolution> {
+        assert!(x < 9 && y < 9, "each coordinate must be within range 0..9");
+        assert!((1..=9).contains(&num), "number must be within range 1..=9");
+        let mut pending = vec![(y * 9 + x, num)];
+
+        while let Some((idx, num)) = pending.pop() {
+            let bit = 1 << (num - 1);
+            if self.masks[idx] & bit == 0 {
+                return Err(NoSolution);
+            }
+            match self.sudoku.xs[idx] {
+                Some(value) if value.value() == num => continue,
+                Some(_) => return Err(NoSolution),
+                None => {}
+            }
+            self.sudoku.xs[idx] = Some(Edited(num));
+            self.masks[idx] = bit;
+
+            let peers: Vec<usize> = self
+                .sudoku
+                .units_of(idx)
+                .flat_map(|unit| self.sudoku.unit_cells(unit).to_vec())
+                .filter(|&i| i != idx)
+                .collect();
+            for i in peers {
+                if self.masks[i] & bit == 0 {
+                    continue;
+                }
+                if self.sudoku.xs[i].is_some() {
+                    return Err(NoSolution);
+                }
+                self.masks[i] &= !bit;
+                match self.masks[i].count_ones() {
+                    0 => return Err(NoSolution),
+                    1 => pending.push((i, self.masks[i].trailing_zeros() as u8 + 1)),
+                    _ => {}
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Removes candidates with one pass of the basic techniques, without assigning any digits, and returns true if any candidate was removed. Repeating it until it returns false advances the candidates one visible step at a time.
+    ///
+    /// Every deduction of the pass is found from the candidates at its start: the number of each naked single is removed from its peers, the other candidates of each hidden single are removed from its cell, and the eliminations of block and line intersections found by [`Sudoku::pointing`] are applied.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use newdoku::Sudoku;
+    /// use std::str::FromStr;
+    ///
+    /// let s = Sudoku::from_str(&format!(".23456789{}", ".".repeat(72))).unwrap();
+    /// let mut grid = s.candidate_grid();
+    /// assert!(grid.candidates(0, 1).contains(&1));
+    /// assert!(grid.candidate_elimination_step());
+    /// assert!(!grid.candidates(0, 1).contains(&1));
+    /// while grid.candidate_elimination_step() {}
+    /// assert!(!grid.candidate_elimination_step());
+    /// ```
+    pub fn candidate_elimination_step(&mut self) -> bool {
+        let houses = self.sudoku.houses();
+        let mut masks = self.masks;
+        for (mask, x) in masks.iter_mut().zip(self.sudoku.xs.iter()) {
+            if x.is_some() {
+                *mask = 0;
+            }
+        }
+        let mut next = masks;
+
+        for idx in (0..81).filter(|&idx| masks[idx].count_ones() == 1) {
+            for unit in self.sudoku.units_of(idx) {
+                for &i in self.sudoku.unit_cells(unit).iter().filter(|&&i| i != idx) {
+                    next[i] &= !masks[idx];
+                }
+            }
+        }
+        for house in &houses {
+            for num in 1..10 {
+                let bit = 1 << (num - 1);
+                let mut cells = house.iter().filter(|&&idx| masks[idx] & bit != 0);
+                if let (Some(&idx), None) = (cells.next(), cells.next()) {
+                    next[idx] &= bit;
+                }
+            }
+        }
+        for e in logic::pointing(&masks, &houses) {
+            next[e.loc.1 * 9 + e.loc.0] &= !(1 << (e.num - 1));
+        }
+
+        let mut changed = false;
+        for idx in (0..81).filter(|&idx| self.sudoku.xs[idx].is_none()) {
+            changed |= next[idx] != self.masks[idx];
+            self.masks[idx] = next[idx];
+        }
+        changed
+    }
+}
+
+impl Sudoku {
+    /// Returns a [`CandidateGrid`] holding `self` and the candidates of each of its cells.
+    pub fn candidate_grid(&self) -> CandidateGrid {
+        let mut masks = self.candidate_masks();
+        for (mask, x) in masks.iter_mut().zip(self.xs.iter()) {
+            if let Some(num) = x {
+                *mask = 1 << (num.value() - 1);
+            }
+        }
+        CandidateGrid {
+            sudoku: self.clone(),
+            masks,
+        }
+    }
+
+    /// Returns a plain text drawing of block `box_index` alone, counted left to right and then top to bottom from 0, for inspecting one area of the grid while debugging.
+    ///
+    /// Each empty cell is drawn as a 3x3 grid of its candidates with `.` for each number ruled out, a given is drawn as `[n]` and an edit as ` n `. The cells of a region set with [`Sudoku::with_regions`] are drawn three to a line in reading order.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `box_index` is not within range 0..9.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use newdoku::Sudoku;
+    /// use std::str::FromStr;
+    ///
+    /// let s = Sudoku::from_str(&format!(".2.......{}", ".".repeat(72))).unwrap();
+    /// let debug = s.region_debug(0);
+    /// let lines: Vec<&str> = debug.lines().collect();
+    /// assert_eq!(lines[0], "block 1");
+    /// assert_eq!(lines[1], "+-------------+");
+    /// assert_eq!(lines[2], "| 1.3     1.3 |");
+    /// assert_eq!(lines[3], "| 456 [2] 456 |");
+    /// ```
+    pub fn region_debug(&self, box_index: usize) -> String {
+        assert!(box_index < 9, "box_index must be within range 0..9");
+        let cells = self.unit_cells(Unit::Block(box_index));
+        let border = format!("+{}+\n", "-".repeat(13));
+        let mut debug = format!("{}\n{}", Unit::Block(box_index), border);
+        for (row, cells) in cells.chunks(3).enumerate() {
+            if row > 0 {
+                debug.push_str(&format!("|{}|\n", " ".repeat(13)));
+            }
+            for line in 0..3 {
+                debug.push_str("| ");
+                for &idx in cells {
+                    let cell = match self.xs[idx] {
+                        Some(Original(num)) if line == 1 => format!("[{}]", num),
+                        Some(Edited(num)) if line == 1 => format!(" {} ", num),
+                        Some(_) => String::from("   "),
+                        None => (line * 3 + 1..=line * 3 + 3)
+                            .map(|num| match self.candidate_mask(idx) & 1 << (num - 1) {
+                                0 => '.',
+                                _ => char::from(b'0' + num as u8),
+                            })
+                            .collect(),
+                    };
+                    debug.push_str(&cell);
+                    debug.push(' ');
+                }
+                debug.push_str("|\n");
+            }
+        }
+        debug.push_str(&border);
+        debug
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::str::FromStr;
+    const TEST_SUDOKU: &str =
+        "xxxxxxx9xx9x7xx21xxx4x9xxxxx1xxx8xxx7xx42xxx5xx8xxxx748x1xxxx4xxxxxxxxxxxx9613xxx";
+    const SOLVED_SUDOKU: &str =
+        "157832496396745218284196753415378962763429185928561374831257649672984531549613827";
+
+    #[test]
+    fn region_debug() {
+        let s = Sudoku::from_str(TEST_SUDOKU)
+            .unwrap()
+            .try_insert((8, 0), 6)
+            .unwrap();
+        let debug = s.region_debug(2);
+        let lines: Vec<&str> = debug.lines().collect();
+        assert_eq!(lines.len(), 14);
+        assert_eq!(lines[0], "block 3");
+        assert_eq!(lines[3], "| 45. [9]  6  |");
+        assert_eq!(lines[5], "|             |");
+        assert_eq!(lines[13], lines[1]);
+        assert!(lines[1..].iter().all(|line| line.len() == 15));
+    }
+
+    #[test]
+    #[should_panic(expected = "box_index")]
+    fn region_debug_out_of_range() {
+        Sudoku::from_str(TEST_SUDOKU).unwrap().region_debug(9);
+    }
+
+    /// Checks that the maintained candidates of every empty cell match those recomputed from scratch.
+    fn assert_consistent(grid: &CandidateGrid) {
+        for idx in (0..81).filter(|&idx| grid.sudoku.xs[idx].is_none()) {
+            assert_eq!(
+                grid.masks[idx],
+                grid.sudoku.candidate_mask(idx),
+                "cell {}",
+                idx
+            );
+        }
+    }
+
+    #[test]
+    fn assign_propagates() {
+        let mut grid = Sudoku::from_str(TEST_SUDOKU).unwrap().candidate_grid();
+        assert_consistent(&grid);
+        for (idx, c) in SOLVED_SUDOKU.bytes().enumerate().take(20) {
+            grid.assign(idx % 9, idx / 9, c - b'0').unwrap();
+            assert_consistent(&grid);
+        }
+        assert!(grid.sudoku().is_valid());
+        assert_eq!(grid.candidates(0, 0), vec![1]);
+    }
+
+    #[test]
+    fn assign_contradiction() {
+        let mut grid = Sudoku::from_str(TEST_SUDOKU).unwrap().candidate_grid();
+        assert_eq!(grid.assign(0, 0, 9), Err(NoSolution));
+
+        let mut grid = Sudoku::from_str(TEST_SUDOKU).unwrap().candidate_grid();
+        assert_eq!(grid.assign(7, 0, 9), Ok(()));
+        assert_eq!(grid.assign(7, 0, 8), Err(NoSolution));
+    }
+
+    #[test]
+    fn candidate_elimination_step() {
+        let s = Sudoku::from_str(&format!(
+            "{}234......567......{}",
+            ".".repeat(9),
+            ".".repeat(54)
+        ))
+        .unwrap();
+        let mut grid = s.candidate_grid();
+        assert!(grid.candidates(8, 0).contains(&1));
+        assert!(grid.candidate_elimination_step());
+        assert!(!grid.candidates(8, 0).contains(&1));
+        assert_eq!(grid.sudoku(), &s);
+
+        // Every pass only removes candidates, and those of the solution are never removed.
+        let mut grid = Sudoku::from_str(TEST_SUDOKU).unwrap().candidate_grid();
+        let mut passes = 0;
+        loop {
+            let before = grid.masks;
+            let changed = grid.candidate_elimination_step();
+            assert_eq!(changed, before != grid.masks);
+            for (idx, c) in SOLVED_SUDOKU.bytes().enumerate() {
+                assert_eq!(grid.masks[idx] & !before[idx], 0);
+                assert_ne!(grid.masks[idx] & 1 << (c - b'1'), 0, "cell {}", idx);
+            }
+            if !changed {
+                break;
+            }
+            passes += 1;
+        }
+        assert!(passes > 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "each coordinate must be within range 0..9")]
+    fn mask_out_of_range() {
+        Sudoku::from_str(TEST_SUDOKU)
+            .unwrap()
+            .candidate_grid()
+            .mask(9, 0);
+    }
+}
@@ -0,0 +1,1141 @@
+use crate::{Sudoku, SudokuNum, SymbolSet};
+use crossterm::{
+    cursor, queue,
+    style::{Attribute, Color, SetForegroundColor},
+    terminal::{self, Clear, ClearType},
+};
+use std::{
+    fmt,
+    io::{self, IsTerminal, Write},
+};
+
+/// Side length of a block, in cells.
+const BOX: usize = 3;
+/// Side length of the grid, in cells.
+const SIZE: usize = BOX * BOX;
+
+/// Returns true if `c` is a graphic character which every terminal draws in a single column.
+///
+/// Only printable ASCII qualifies, since even Latin-1 symbols such as `·` and `°` have ambiguous width and are drawn in two columns by CJK terminals. Every glyph drawn in a cell must qualify, so each cell of a grid is exactly one column wide.
+pub(crate) fn is_single_width(c: char) -> bool {
+    c.is_ascii_graphic()
+}
+
+/// Returns the number of terminal columns `s` occupies, ignoring ANSI escape sequences.
+pub(crate) fn visible_width(s: &str) -> usize {
+    let mut width = 0;
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            // Skip up to and including the final byte of a control sequence.
+            for c in chars.by_ref() {
+                if c.is_ascii_alphabetic() {
+                    break;
+                }
+            }
+        } else {
+            width += 1;
+        }
+    }
+    width
+}
+
+/// Returns the column and line of the cell at `idx` within the grid of `sudoku` drawn by the [`Display`](fmt::Display) impl of [`Sudoku`].
+pub(crate) fn cell_offset(sudoku: &Sudoku, idx: usize) -> (u16, u16) {
+    let (x, y) = (idx % SIZE, idx / SIZE);
+    match sudoku.variants.jigsaw {
+        Some(_) => ((2 + x * 4) as u16, (1 + y * 2) as u16),
+        None => ((2 + x * 2 + x / BOX * 2) as u16, (1 + y + y / BOX) as u16),
+    }
+}
+
+/// Draws the frames of the solving animation, rewriting only the cells which changed since the previous frame when it can.
+#[derive(Debug)]
+pub(crate) struct Animation {
+    /// Terminal size when the previous frame was drawn.
+    size: Option<(u16, u16)>,
+    /// The previous frame and the terminal position of its top left corner, if later frames can be drawn over it cell by cell.
+    previous: Option<(Sudoku, (u16, u16))>,
+    /// Whether the cursor position can be read, which is false once reading it has failed so that every frame does not wait for it.
+    can_position: bool,
+    /// Theme each frame is drawn with.
+    theme: Theme,
+}
+
+impl Animation {
+    /// Returns an animation drawn with `theme` which has not drawn any frames, where `size` is the current size of the terminal.
+    pub(crate) fn new(size: Option<(u16, u16)>, theme: Theme) -> Self {
+        Self {
+            size,
+            previous: None,
+            can_position: io::stdout().is_terminal(),
+            theme,
+        }
+    }
+
+    /// Draws `sudoku` as the next frame, leaving the cursor at the top of the frame so that the next frame overwrites it.
+    ///
+    /// If the terminal has been resized since the previous frame, the old frame is cleared and the whole grid is drawn again. The whole grid is also drawn for the first frame, or if the terminal is too narrow to hold a line of the grid or the cursor position cannot be read from it.
+    pub(crate) fn draw(&mut self, sudoku: &Sudoku) {
+        let current = terminal::size().ok();
+        if current != self.size {
+            print!("{}", Clear(ClearType::FromCursorDown));
+            self.size = current;
+            self.previous = None;
+        }
+
+        let (previous, (left, top)) = match &mut self.previous {
+            Some((previous, origin)) => (previous, *origin),
+            None => {
+                let grid = sudoku.display_with(&self.theme).to_string();
+                draw_frame(&grid, current);
+                let width = grid.lines().map(visible_width).max();
+                let fits = match (self.size, width) {
+                    (Some((cols, _)), Some(width)) => cols as usize >= width,
+                    _ => false,
+                };
+                if fits && self.can_position {
+                    self.previous = cursor::position()
+                        .ok()
+                        .map(|origin| (sudoku.clone(), origin));
+                    self.can_position = self.previous.is_some();
+                }
+                return;
+            }
+        };
+
+        let mut stdout = io::stdout();
+        for idx in (0..SIZE * SIZE).filter(|&idx| sudoku.xs[idx] != previous.xs[idx]) {
+            let (col, line) = cell_offset(sudoku, idx);
+            let _ = queue!(stdout, cursor::MoveTo(left + col, top + line));
+            let _ = match sudoku.xs[idx] {
+                Some(SudokuNum::Original(num)) if self.theme.color => write!(
+                    stdout,
+                    "{}{}{}",
+                    Attribute::Bold,
+                    self.theme.glyph(num),
+                    Attribute::Reset
+                ),
+                Some(num) => write!(stdout, "{}", self.theme.glyph(num.value())),
+                None => write!(stdout, "{}", self.theme.blank),
+            };
+        }
+        let _ = queue!(stdout, cursor::MoveTo(left, top));
+        let _ = stdout.flush();
+        *previous = sudoku.clone();
+    }
+}
+
+/// Draws `grid` as a whole frame of the solving animation, leaving the cursor at the top of the frame so that the next frame overwrites it.
+///
+/// Lines too wide for a terminal of the given `size` are assumed to wrap, and if the size is unknown then no wrapping is assumed.
+fn draw_frame(grid: &str, size: Option<(u16, u16)>) {
+    let rows: usize = grid
+        .lines()
+        .map(|line| match size {
+            Some((cols, _)) if cols > 0 => visible_width(line).max(1).div_ceil(cols as usize),
+            _ => 1,
+        })
+        .sum();
+    // The frame is followed by a blank line, and the final newline moves back down onto the first row.
+    println!("{}\n\n{}", grid, cursor::MoveUp(rows as u16 + 2));
+}
+
+/// Returns the horizontal line between bands of blocks, `block` columns wide above each block, with junctions joining the vertical lines `up` and `down` from it.
+fn rule(border: Border, block: usize, up: bool, down: bool) -> String {
+    let segment = border.horizontal().to_string().repeat(block);
+    (0..=BOX)
+        .map(|b| match b {
+            BOX => border.junction(up, down, true, false).to_string(),
+            b => format!("{}{}", border.junction(up, down, b > 0, true), segment),
+        })
+        .collect()
+}
+
+/// Writes the bordered grid of `sudoku` with the lines of `border`, calling `cell` with each cell index to write its single column of content. The borders of a jigsaw sudoku are drawn by [`write_regions`].
+pub(crate) fn write_grid<F>(
+    f: &mut fmt::Formatter,
+    sudoku: &Sudoku,
+    border: Border,
+    mut cell: F,
+) -> fmt::Result
+where
+    F: FnMut(&mut fmt::Formatter, usize) -> fmt::Result,
+{
+    if sudoku.variants.jigsaw.is_some() {
+        return write_regions(f, sudoku, border, cell);
+    }
+    let block = BOX * 2 + 1;
+    let vertical = border.vertical();
+    for y in 0..SIZE {
+        if y % BOX == 0 {
+            writeln!(f, "{}", rule(border, block, y > 0, true))?;
+        }
+        for x in 0..SIZE {
+            if x % BOX == 0 {
+                write!(f, "{} ", vertical)?;
+            }
+            cell(f, y * SIZE + x)?;
+            write!(f, " ")?;
+        }
+        writeln!(f, "{}", vertical)?;
+    }
+    write!(f, "{}", rule(border, block, true, false))
+}
+
+/// Writes the grid of a jigsaw sudoku like [`write_grid`], with every cell separated from the next and a border drawn only between cells of different regions.
+fn write_regions<F>(
+    f: &mut fmt::Formatter,
+    sudoku: &Sudoku,
+    border: Border,
+    mut cell: F,
+) -> fmt::Result
+where
+    F: FnMut(&mut fmt::Formatter, usize) -> fmt::Result,
+{
+    // Region of the cell at `(x, y)`, where coordinates of -1 or 9 lie outside the grid.
+    let region = |x: isize, y: isize| match (0..SIZE as isize).contains(&x)
+        && (0..SIZE as isize).contains(&y)
+    {
+        true => Some(sudoku.block_of(y as usize * SIZE + x as usize)),
+        false => None,
+    };
+    let apart = |a: (isize, isize), b: (isize, isize)| region(a.0, a.1) != region(b.0, b.1);
+
+    for y in 0..=SIZE as isize {
+        // The border above row `y`, with a junction joining whichever borders meet at each corner.
+        for x in 0..=SIZE as isize {
+            let up = apart((x - 1, y - 1), (x, y - 1));
+            let down = apart((x - 1, y), (x, y));
+            let left = apart((x - 1, y - 1), (x - 1, y));
+            let right = apart((x, y - 1), (x, y));
+            write!(f, "{}", border.junction(up, down, left, right))?;
+            if x < SIZE as isize {
+                let edge = if right { border.horizontal() } else { ' ' };
+                write!(f, "{}", edge.to_string().repeat(3))?;
+            }
+        }
+        if y == SIZE as isize {
+            return Ok(());
+        }
+
+        writeln!(f)?;
+        for x in 0..SIZE as isize {
+            let edge = if apart((x - 1, y), (x, y)) {
+                border.vertical()
+            } else {
+                ' '
+            };
+            write!(f, "{} ", edge)?;
+            cell(f, y as usize * SIZE + x as usize)?;
+            write!(f, " ")?;
+        }
+        writeln!(f, "{}", border.vertical())?;
+    }
+    Ok(())
+}
+
+/// The characters the lines of a grid are drawn with by a [`Theme`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Border {
+    /// `+`, `-` and `|`, which every terminal can draw.
+    #[default]
+    Ascii,
+    /// Box-drawing characters such as `┼`, `─` and `│`, which join into continuous lines but need a font that has them.
+    Unicode,
+}
+
+impl Border {
+    /// Returns the character drawn where lines meet, given which of the lines above, below, left and right of it are drawn.
+    fn junction(self, up: bool, down: bool, left: bool, right: bool) -> char {
+        match self {
+            Self::Ascii => match (left || right, up || down) {
+                (true, true) => '+',
+                (true, false) => '-',
+                (false, true) => '|',
+                (false, false) => ' ',
+            },
+            Self::Unicode => match (up, down, left, right) {
+                (false, false, false, false) => ' ',
+                (_, _, false, false) => '│',
+                (false, false, _, _) => '─',
+                (false, true, false, true) => '┌',
+                (false, true, true, false) => '┐',
+                (true, false, false, true) => '└',
+                (true, false, true, false) => '┘',
+                (true, true, false, true) => '├',
+                (true, true, true, false) => '┤',
+                (false, true, true, true) => '┬',
+                (true, false, true, true) => '┴',
+                (true, true, true, true) => '┼',
+            },
+        }
+    }
+
+    /// Returns the character of a horizontal line.
+    fn horizontal(self) -> char {
+        match self {
+            Self::Ascii => '-',
+            Self::Unicode => '─',
+        }
+    }
+
+    /// Returns the character of a vertical line.
+    fn vertical(self) -> char {
+        match self {
+            Self::Ascii => '|',
+            Self::Unicode => '│',
+        }
+    }
+}
+
+/// Options controlling how a [`Sudoku`] is displayed by [`Sudoku::display_with`].
+///
+/// Only single-column glyphs, which is to say printable ASCII, are supported for blanks and numbers, so every cell is drawn exactly one column wide.
+///
+/// # Examples
+///
+/// ```
+/// use newdoku::{Sudoku, Theme};
+/// use std::str::FromStr;
+///
+/// let s = Sudoku::from_str(&".".repeat(81)).unwrap();
+/// let theme = Theme::new().blank('_');
+/// assert!(s.display_with(&theme).to_string().contains("| _ _ _ |"));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Theme {
+    blank: char,
+    color: bool,
+    symbols: SymbolSet,
+    border: Border,
+}
+
+const DEFAULT_THEME: Theme = Theme {
+    blank: '.',
+    color: true,
+    symbols: SymbolSet::DIGITS,
+    border: Border::Ascii,
+};
+
+impl Default for Theme {
+    fn default() -> Self {
+        DEFAULT_THEME
+    }
+}
+
+impl Theme {
+    /// Returns the default theme, which matches the [`Display`](fmt::Display) impl of [`Sudoku`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the glyph shown for empty cells. Defaults to `.`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `blank` might be drawn wider than a single terminal column, which would misalign the grid. Only printable ASCII other than the space is accepted, since CJK terminals draw other characters, even those of ambiguous width such as `·`, in two columns.
+    pub fn blank(mut self, blank: char) -> Self {
+        assert!(
+            is_single_width(blank),
+            "the blank glyph must be a single-width graphic character"
+        );
+        self.blank = blank;
+        self
+    }
+
+    /// Sets whether ANSI escapes are written, which make givens bold and colour [`Sudoku::display_heatmap_with`]. Defaults to true. Turning it off gives plain text for contexts which are not terminals, such as logs and files.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use newdoku::{Sudoku, Theme};
+    /// use std::str::FromStr;
+    ///
+    /// let s = Sudoku::from_str(&format!("1{}", ".".repeat(80))).unwrap();
+    /// let plain = s.display_with(&Theme::new().color(false)).to_string();
+    /// assert!(!plain.contains('\x1b'));
+    /// assert!(plain.contains("| 1 . . |"));
+    /// ```
+    pub fn color(mut self, color: bool) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// Sets the characters shown for the numbers 1 to 9, so that a grid read with [`Sudoku::from_str_with`] is drawn with the same symbols. Defaults to [`SymbolSet::DIGITS`]. Candidate counts in [`Sudoku::display_heatmap_with`] are still drawn as digits.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use newdoku::{Sudoku, SymbolSet, Theme};
+    ///
+    /// let letters = SymbolSet::new(['A', 'B', 'C', 'D', 'E', 'F', 'G', 'H', 'I']).unwrap();
+    /// let s = Sudoku::from_str_with(&format!("A.B{}", ".".repeat(78)), &letters).unwrap();
+    /// let theme = Theme::new().symbols(letters).color(false);
+    /// assert!(s.display_with(&theme).to_string().contains("| A . B |"));
+    /// ```
+    pub fn symbols(mut self, symbols: SymbolSet) -> Self {
+        self.symbols = symbols;
+        self
+    }
+
+    /// Sets the characters the lines of the grid are drawn with. Defaults to [`Border::Ascii`], since box-drawing characters are missing from some fonts and drawn two columns wide by CJK terminals.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use newdoku::{Border, Sudoku, Theme};
+    /// use std::str::FromStr;
+    ///
+    /// let s = Sudoku::from_str(&".".repeat(81)).unwrap();
+    /// let display = s.display_with(&Theme::new().border(Border::Unicode)).to_string();
+    /// assert!(display.starts_with("┌───────┬"));
+    /// assert!(display.contains("│ . . . │"));
+    /// ```
+    pub fn border(mut self, border: Border) -> Self {
+        self.border = border;
+        self
+    }
+
+    /// Returns the glyph drawn for `num`.
+    pub(crate) fn glyph(&self, num: u8) -> char {
+        self.symbols.symbol(num)
+    }
+
+    /// Writes `glyph`, in bold if this theme is coloured.
+    pub(crate) fn write_bold(&self, f: &mut fmt::Formatter, glyph: char) -> fmt::Result {
+        match self.color {
+            true => write!(f, "{}{}{}", Attribute::Bold, glyph, Attribute::Reset),
+            false => write!(f, "{}", glyph),
+        }
+    }
+}
+
+/// Displays a [`Sudoku`] using a [`Theme`], optionally leaving edited cells blank.
+struct Themed<'a> {
+    sudoku: &'a Sudoku,
+    theme: &'a Theme,
+    givens_only: bool,
+}
+
+impl fmt::Display for Themed<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        use SudokuNum::*;
+        write_grid(f, self.sudoku, self.theme.border, |f, i| {
+            match self.sudoku.xs[i] {
+                Some(Original(num)) => self.theme.write_bold(f, self.theme.glyph(num)),
+                Some(Edited(num)) if !self.givens_only => write!(f, "{}", self.theme.glyph(num)),
+                _ => write!(f, "{}", self.theme.blank),
+            }
+        })
+    }
+}
+
+/// Displays a [`Sudoku`] with each empty cell showing its candidate count in a colour reflecting how easy it is to fill.
+struct Heatmap<'a> {
+    sudoku: &'a Sudoku,
+    theme: &'a Theme,
+}
+
+impl fmt::Display for Heatmap<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        use SudokuNum::*;
+        let masks = self.sudoku.candidate_masks();
+
+        write_grid(f, self.sudoku, self.theme.border, |f, i| {
+            match self.sudoku.xs[i] {
+                Some(Original(num)) => self.theme.write_bold(f, self.theme.glyph(num)),
+                Some(Edited(num)) => write!(f, "{}", self.theme.glyph(num)),
+                None if !self.theme.color => write!(f, "{}", masks[i].count_ones()),
+                None => {
+                    let count = masks[i].count_ones();
+                    let color = match count {
+                        0 => Color::Red,
+                        1 => Color::Green,
+                        2 => Color::Yellow,
+                        _ => Color::DarkGrey,
+                    };
+                    write!(
+                        f,
+                        "{}{}{}",
+                        SetForegroundColor(color),
+                        count,
+                        Attribute::Reset
+                    )
+                }
+            }
+        })
+    }
+}
+
+/// Displays a [`Sudoku`] with column numbers across the top and row numbers down the left, counting from 1.
+struct Labeled<'a> {
+    sudoku: &'a Sudoku,
+    theme: &'a Theme,
+}
+
+impl fmt::Display for Labeled<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        // Each column number sits above the cells of its column, after the two columns of row numbers.
+        let mut header = String::new();
+        for x in 0..SIZE {
+            let col = 2 + cell_offset(self.sudoku, x).0 as usize;
+            header.push_str(&format!("{:>1$}", x + 1, col + 1 - header.len()));
+        }
+        write!(f, "{}", header)?;
+
+        let grid = self.sudoku.display_with(self.theme).to_string();
+        for (i, line) in grid.lines().enumerate() {
+            match (0..SIZE).find(|&y| cell_offset(self.sudoku, y * SIZE).1 as usize == i) {
+                Some(y) => write!(f, "\n{} {}", y + 1, line)?,
+                None => write!(f, "\n  {}", line)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Displays a [`Sudoku`] with each empty cell drawn as a 3x3 grid of its candidates.
+struct Candidates<'a> {
+    sudoku: &'a Sudoku,
+    theme: &'a Theme,
+}
+
+impl fmt::Display for Candidates<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        use SudokuNum::*;
+        let masks = self.sudoku.candidate_masks();
+        // Each cell holds a row of three candidates, and each block three cells separated by spaces.
+        let block = BOX * (BOX + 1) + 1;
+        let border = self.theme.border;
+        let vertical = border.vertical();
+        let spacer =
+            format!("{}{}", vertical, " ".repeat(block)).repeat(BOX) + &vertical.to_string();
+
+        for y in 0..SIZE {
+            match y % BOX {
+                0 => writeln!(f, "{}", rule(border, block, y > 0, true))?,
+                _ => writeln!(f, "{}", spacer)?,
+            }
+            for line in 0..BOX {
+                for x in 0..SIZE {
+                    if x % BOX == 0 {
+                        write!(f, "{} ", vertical)?;
+                    }
+                    let idx = y * SIZE + x;
+                    match self.sudoku.xs[idx] {
+                        Some(num) if line == BOX / 2 => {
+                            write!(f, " ")?;
+                            let glyph = self.theme.glyph(num.value());
+                            if matches!(num, Original(_)) {
+                                self.theme.write_bold(f, glyph)?;
+                            } else {
+                                write!(f, "{}", glyph)?;
+                            }
+                            write!(f, " ")?;
+                        }
+                        Some(_) => write!(f, "{:1$}", "", BOX)?,
+                        None => {
+                            for num in line * BOX + 1..=line * BOX + BOX {
+                                match masks[idx] & 1 << (num - 1) {
+                                    0 => write!(f, "{}", self.theme.blank)?,
+                                    _ => write!(f, "{}", self.theme.glyph(num as u8))?,
+                                }
+                            }
+                        }
+                    }
+                    write!(f, " ")?;
+                }
+                writeln!(f, "{}", vertical)?;
+            }
+        }
+        write!(f, "{}", rule(border, block, true, false))
+    }
+}
+
+/// Columns between grids drawn side by side by [`Sudoku::display_beside`].
+const GAP: usize = 2;
+
+/// Displays several grids side by side, wrapping them into rows no wider than `width`.
+struct Beside<'a> {
+    grids: &'a [Sudoku],
+    theme: &'a Theme,
+    width: usize,
+}
+
+impl fmt::Display for Beside<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        let drawn: Vec<Vec<String>> = self
+            .grids
+            .iter()
+            .map(|grid| {
+                let grid = grid.display_with(self.theme).to_string();
+                grid.lines().map(String::from).collect()
+            })
+            .collect();
+        let widths: Vec<usize> = drawn
+            .iter()
+            .map(|lines| {
+                lines
+                    .iter()
+                    .map(|line| visible_width(line))
+                    .max()
+                    .unwrap_or(0)
+            })
+            .collect();
+
+        // Starting index of each row, with a grid which would overflow the row starting the next.
+        let mut starts = Vec::new();
+        let mut used = 0;
+        for (i, &width) in widths.iter().enumerate() {
+            if i == 0 || used + GAP + width > self.width {
+                starts.push(i);
+                used = width;
+            } else {
+                used += GAP + width;
+            }
+        }
+        starts.push(drawn.len());
+
+        for (row, range) in starts.windows(2).enumerate() {
+            if row > 0 {
+                write!(f, "\n\n")?;
+            }
+            let height = drawn[range[0]..range[1]]
+                .iter()
+                .map(Vec::len)
+                .max()
+                .unwrap_or(0);
+            for line in 0..height {
+                if line > 0 {
+                    writeln!(f)?;
+                }
+                for i in range[0]..range[1] {
+                    let text = drawn[i].get(line).map_or("", String::as_str);
+                    write!(f, "{}", text)?;
+                    if i + 1 < range[1] {
+                        let pad = widths[i] - visible_width(text) + GAP;
+                        write!(f, "{:1$}", "", pad)?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Sudoku {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        self.display_with(&DEFAULT_THEME).fmt(f)
+    }
+}
+
+impl Sudoku {
+    /// Returns a displayable grid of `self` drawn using `theme`.
+    pub fn display_with<'a>(&'a self, theme: &'a Theme) -> impl fmt::Display + 'a {
+        Themed {
+            sudoku: self,
+            theme,
+            givens_only: false,
+        }
+    }
+
+    /// Returns a displayable grid of `self` with column numbers across the top and row numbers down the left, so that cells can be referred to as e.g. R3C5. Rows and columns are numbered from 1.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use newdoku::Sudoku;
+    /// use std::str::FromStr;
+    ///
+    /// let s = Sudoku::from_str(&".".repeat(81)).unwrap();
+    /// let display = s.display_labeled().to_string();
+    /// assert!(display.starts_with("    1 2 3   4 5 6   7 8 9\n  +-------+"));
+    /// assert!(display.contains("\n3 | . . . |"));
+    /// ```
+    pub fn display_labeled(&self) -> impl fmt::Display + '_ {
+        self.display_labeled_with(&DEFAULT_THEME)
+    }
+
+    /// Returns a displayable grid of `self` with row and column numbers like [`Sudoku::display_labeled`], drawn using `theme`.
+    pub fn display_labeled_with<'a>(&'a self, theme: &'a Theme) -> impl fmt::Display + 'a {
+        Labeled {
+            sudoku: self,
+            theme,
+        }
+    }
+
+    /// Returns a displayable grid of `self` in which each empty cell is drawn as a 3x3 grid of its candidates, with `.` for each number ruled out, and each filled cell shows its number in the middle. Cells are separated by spacer lines within a block.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use newdoku::Sudoku;
+    /// use std::str::FromStr;
+    ///
+    /// let s = Sudoku::from_str(&format!(".23456789{}", ".".repeat(72))).unwrap();
+    /// let lines: Vec<String> = s.display_candidates().to_string().lines().map(String::from).collect();
+    /// assert_eq!(lines.len(), 37);
+    /// assert!(lines[1].starts_with("| 1.. "));
+    /// assert!(lines[4].starts_with("|     "));
+    /// ```
+    pub fn display_candidates(&self) -> impl fmt::Display + '_ {
+        self.display_candidates_with(&DEFAULT_THEME)
+    }
+
+    /// Returns a displayable grid of the candidates of `self` like [`Sudoku::display_candidates`], showing each number ruled out with the blank glyph of `theme`.
+    pub fn display_candidates_with<'a>(&'a self, theme: &'a Theme) -> impl fmt::Display + 'a {
+        Candidates {
+            sudoku: self,
+            theme,
+        }
+    }
+
+    /// Returns a displayable grid of `self` in which each empty cell shows how many candidates it has, coloured green for one, yellow for two, grey for more and red for none.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use newdoku::Sudoku;
+    /// use std::str::FromStr;
+    ///
+    /// let s = Sudoku::from_str(&format!(".23456789{}", ".".repeat(72))).unwrap();
+    /// assert!(s.display_heatmap().to_string().lines().nth(1).unwrap().contains('1'));
+    /// ```
+    pub fn display_heatmap(&self) -> impl fmt::Display + '_ {
+        self.display_heatmap_with(&DEFAULT_THEME)
+    }
+
+    /// Returns a displayable grid of the candidate counts of `self` like [`Sudoku::display_heatmap`], with cells as wide as those drawn using `theme`.
+    pub fn display_heatmap_with<'a>(&'a self, theme: &'a Theme) -> impl fmt::Display + 'a {
+        Heatmap {
+            sudoku: self,
+            theme,
+        }
+    }
+
+    /// Returns `self` drawn as a large board for presentations, with heavy borders around the blocks and each cell padded to `2 * scale + 1` columns by `2 * scale - 1` lines. Givens are bold, and a `scale` of 0 is treated as 1.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use newdoku::Sudoku;
+    /// use std::str::FromStr;
+    ///
+    /// let s = Sudoku::from_str(&".".repeat(81)).unwrap();
+    /// let board = s.to_pretty_ansi(2);
+    /// assert_eq!(board.lines().count(), 31);
+    /// assert!(board.starts_with("┏━━━━━━━━━━━━━━━┳"));
+    /// ```
+    pub fn to_pretty_ansi(&self, scale: usize) -> String {
+        use SudokuNum::*;
+        let scale = scale.max(1);
+        let (width, height) = (2 * scale + 1, 2 * scale - 1);
+        let border = |left: char, mid: char, right: char| {
+            let segment = "━".repeat(3 * width);
+            format!("{left}{segment}{mid}{segment}{mid}{segment}{right}")
+        };
+        let pad = " ".repeat(scale);
+
+        let mut board = border('┏', '┳', '┓');
+        for y in 0..SIZE {
+            for line in 0..height {
+                board.push_str("\n┃");
+                for x in 0..SIZE {
+                    if line == height / 2 {
+                        let cell = match self.xs[y * SIZE + x] {
+                            Some(Original(num)) => {
+                                format!("{}{}{}", Attribute::Bold, num, Attribute::Reset)
+                            }
+                            Some(Edited(num)) => num.to_string(),
+                            None => String::from("."),
+                        };
+                        board.push_str(&format!("{pad}{cell}{pad}"));
+                    } else {
+                        board.push_str(&" ".repeat(width));
+                    }
+                    if x % BOX == BOX - 1 {
+                        board.push('┃');
+                    }
+                }
+            }
+            match y {
+                8 => board.push_str(&format!("\n{}", border('┗', '┻', '┛'))),
+                _ if y % BOX == BOX - 1 => board.push_str(&format!("\n{}", border('┣', '╋', '┫'))),
+                _ => {}
+            }
+        }
+        board
+    }
+
+    /// Returns a display of `grids` drawn using `theme` and placed side by side, as many to a row as fit within `width` columns, with a blank line between rows. A grid wider than `width` gets a row of its own, so a `width` of 0 draws one grid per row.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use newdoku::{Sudoku, Theme};
+    /// use std::str::FromStr;
+    ///
+    /// let s = Sudoku::from_str(&".".repeat(81)).unwrap();
+    /// let theme = Theme::new().color(false);
+    /// let grids = vec![s.clone(), s.clone(), s];
+    /// let display = Sudoku::display_beside(&grids, &theme, 60).to_string();
+    /// let lines: Vec<&str> = display.lines().collect();
+    /// assert_eq!(lines.len(), 27);
+    /// assert_eq!(lines[0], format!("{0}  {0}", "+-------".repeat(3) + "+"));
+    /// assert_eq!(lines[13], "");
+    /// ```
+    pub fn display_beside<'a>(
+        grids: &'a [Sudoku],
+        theme: &'a Theme,
+        width: usize,
+    ) -> impl fmt::Display + 'a {
+        Beside {
+            grids,
+            theme,
+            width,
+        }
+    }
+
+    /// Returns a displayable grid of only the givens of `self`, with every edited cell shown as blank.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use newdoku::Sudoku;
+    /// use std::str::FromStr;
+    ///
+    /// let puzzle = Sudoku::from_str(&format!("1{}", ".".repeat(80))).unwrap();
+    /// let edited = puzzle.try_insert((1, 0), 2).unwrap();
+    /// assert_eq!(edited.display_givens().to_string(), puzzle.to_string());
+    /// ```
+    pub fn display_givens(&self) -> impl fmt::Display + '_ {
+        Themed {
+            sudoku: self,
+            theme: &DEFAULT_THEME,
+            givens_only: true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn single_width() {
+        for c in ['5', '.', '_', '#'] {
+            assert!(is_single_width(c), "{:?}", c);
+        }
+        for c in [' ', '\t', '\u{B7}', 'é', '\u{301}', '□', '五', '５', '🙂'] {
+            assert!(!is_single_width(c), "{:?}", c);
+        }
+    }
+
+    /// Returns a jigsaw sudoku holding `line`, with `(2, 0)` moved into the second region and `(3, 1)` into the first.
+    fn jigsaw(line: &str) -> Sudoku {
+        let mut region_of: [usize; 81] = core::array::from_fn(|i| i / 27 * 3 + i % 9 / 3);
+        region_of.swap(2, 12);
+        Sudoku::from_str(line)
+            .unwrap()
+            .with_regions(region_of)
+            .unwrap()
+    }
+
+    #[test]
+    fn cell_offsets() {
+        let line = "123456789".repeat(9);
+        for sudoku in [Sudoku::from_str(&line).unwrap(), jigsaw(&line)] {
+            let s = sudoku.to_string();
+            let lines: Vec<&str> = s.lines().collect();
+            for idx in [0, 4, 8, 30, 44, 80] {
+                let (col, line) = cell_offset(&sudoku, idx);
+                let line = lines[line as usize]
+                    .replace(&Attribute::Bold.to_string(), "")
+                    .replace(&Attribute::Reset.to_string(), "");
+                assert_eq!(
+                    line.as_bytes()[col as usize],
+                    b'1' + (idx % 9) as u8,
+                    "cell {}",
+                    idx
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn visible_widths() {
+        assert_eq!(visible_width("+---+"), 5);
+        assert_eq!(
+            visible_width(&format!("| {}1{} |", Attribute::Bold, Attribute::Reset)),
+            5
+        );
+        let s = Sudoku::from_str(&"1........".repeat(9)).unwrap();
+        assert!(s.to_string().lines().all(|line| visible_width(line) == 25));
+    }
+
+    #[test]
+    fn display_heatmap() {
+        let s = Sudoku::from_str(&format!(".23456789{}", ".".repeat(72))).unwrap();
+        let lines: Vec<String> = s
+            .display_heatmap()
+            .to_string()
+            .lines()
+            .map(String::from)
+            .collect();
+        assert_eq!(lines.len(), 13);
+        assert!(lines[1].starts_with(&format!(
+            "| {}1{} {}2{}",
+            SetForegroundColor(Color::Green),
+            Attribute::Reset,
+            Attribute::Bold,
+            Attribute::Reset
+        )));
+        assert!(lines[2].contains(&format!(
+            "{}6{}",
+            SetForegroundColor(Color::DarkGrey),
+            Attribute::Reset
+        )));
+        assert_eq!(visible_width(&lines[1]), 25);
+    }
+
+    #[test]
+    fn to_pretty_ansi() {
+        let s = Sudoku::from_str(&format!("{}1", ".".repeat(80)))
+            .unwrap()
+            .try_insert((0, 0), 2)
+            .unwrap();
+        let board = s.to_pretty_ansi(1);
+        let lines: Vec<&str> = board.lines().collect();
+        assert_eq!(lines.len(), 13);
+        assert_eq!(lines[0], format!("┏{0}┳{0}┳{0}┓", "━".repeat(9)));
+        assert_eq!(lines[1], "┃ 2  .  . ┃ .  .  . ┃ .  .  . ┃");
+        assert_eq!(lines[4], format!("┣{0}╋{0}╋{0}┫", "━".repeat(9)));
+        assert!(lines[11].ends_with(&format!("{}1{} ┃", Attribute::Bold, Attribute::Reset)));
+        assert_eq!(lines[12], format!("┗{0}┻{0}┻{0}┛", "━".repeat(9)));
+        assert_eq!(s.to_pretty_ansi(0), board);
+
+        let board = s.to_pretty_ansi(3);
+        assert_eq!(board.lines().count(), 9 * 5 + 4);
+        assert!(board
+            .lines()
+            .all(|line| visible_width(line) == 3 * 3 * 7 + 4));
+    }
+
+    #[test]
+    fn display() {
+        let s = Sudoku::from_str(&format!("{}1", ".".repeat(80)))
+            .unwrap()
+            .try_insert((0, 0), 2)
+            .unwrap();
+        let lines: Vec<String> = s.to_string().lines().map(String::from).collect();
+        assert_eq!(lines.len(), 13);
+        assert_eq!(lines[0], "+-------+-------+-------+");
+        assert_eq!(lines[1], "| 2 . . | . . . | . . . |");
+        assert_eq!(
+            lines[11],
+            format!(
+                "| . . . | . . . | . . {}1{} |",
+                Attribute::Bold,
+                Attribute::Reset
+            )
+        );
+        assert_eq!(lines[12], "+-------+-------+-------+");
+        assert_eq!(
+            s.display_givens().to_string().lines().nth(1),
+            Some("| . . . | . . . | . . . |")
+        );
+    }
+
+    #[test]
+    fn display_jigsaw() {
+        let s = jigsaw(&format!("{}1", ".".repeat(80)));
+        let display = s.to_string();
+        let lines: Vec<&str> = display.lines().collect();
+        assert_eq!(lines.len(), 19);
+        assert_eq!(lines[0], "+-------+---------------+-----------+");
+        assert_eq!(lines[1], "| .   . | .   .   .   . | .   .   . |");
+        assert_eq!(lines[2], "|       +-------+       |           |");
+        assert_eq!(lines[3], "| .   .   .   . | .   . | .   .   . |");
+        assert_eq!(lines[4], "|           +---+       |           |");
+        assert_eq!(lines[6], format!("+{0}+{0}+{0}+", "-".repeat(11)));
+        assert_eq!(lines[8], "|           |           |           |");
+        assert!(lines[17].ends_with(&format!("{}1{} |", Attribute::Bold, Attribute::Reset)));
+        assert!(lines.iter().all(|line| visible_width(line) == 37));
+
+        let labeled = s.display_labeled().to_string();
+        let lines: Vec<&str> = labeled.lines().collect();
+        assert_eq!(lines[0], "    1   2   3   4   5   6   7   8   9");
+        assert_eq!(lines[2], "1 | .   . | .   .   .   . | .   .   . |");
+        assert_eq!(lines[3], "  |       +-------+       |           |");
+        assert_eq!(&lines[18][..2], "9 ");
+    }
+
+    #[test]
+    fn display_candidates() {
+        let s = Sudoku::from_str(&format!("{}1", ".".repeat(80)))
+            .unwrap()
+            .try_insert((0, 0), 2)
+            .unwrap();
+        let display = s.display_candidates().to_string();
+        let lines: Vec<&str> = display.lines().collect();
+        assert_eq!(lines.len(), 37);
+        assert_eq!(lines[0], format!("+{0}+{0}+{0}+", "-".repeat(13)));
+        assert_eq!(lines[1], "|     1.3 1.3 | 1.3 1.3 1.3 | 1.3 1.3 ..3 |");
+        assert_eq!(lines[2], "|  2  456 456 | 456 456 456 | 456 456 456 |");
+        assert_eq!(lines[4], format!("|{0}|{0}|{0}|", " ".repeat(13)));
+        assert_eq!(
+            lines[34],
+            format!(
+                "| 456 456 456 | 456 456 456 | 456 456  {}1{}  |",
+                Attribute::Bold,
+                Attribute::Reset
+            )
+        );
+        assert_eq!(lines[33], "| ..3 .23 .23 | .23 .23 .23 | .23 .23     |");
+        assert!(lines.iter().all(|line| visible_width(line) == 43));
+    }
+
+    #[test]
+    fn display_labeled() {
+        let s = Sudoku::from_str(&format!("{}1", ".".repeat(80))).unwrap();
+        let display = s.display_labeled().to_string();
+        let lines: Vec<&str> = display.lines().collect();
+        assert_eq!(lines.len(), 14);
+        assert_eq!(lines[0], "    1 2 3   4 5 6   7 8 9");
+        assert_eq!(lines[1], "  +-------+-------+-------+");
+        assert_eq!(lines[2], "1 | . . . | . . . | . . . |");
+        assert_eq!(&lines[12][..2], "9 ");
+        assert!(lines[12].ends_with(&format!("{}1{} |", Attribute::Bold, Attribute::Reset)));
+        assert_eq!(lines[13], lines[1]);
+
+        // Each column number sits above the cells of its column.
+        for (i, c) in lines[0].char_indices().filter(|(_, c)| c.is_ascii_digit()) {
+            let x = c.to_digit(10).unwrap() as usize - 1;
+            let mut s = Sudoku::from_str(&".".repeat(81)).unwrap();
+            s = s.try_insert((x, 0), 5).unwrap();
+            assert_eq!(
+                s.display_labeled()
+                    .to_string()
+                    .lines()
+                    .nth(2)
+                    .unwrap()
+                    .as_bytes()[i],
+                b'5'
+            );
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "single-width")]
+    fn wide_blank() {
+        let _ = Theme::new().blank('口');
+    }
+
+    #[test]
+    fn display_beside() {
+        let theme = Theme::new().color(false);
+        let s = Sudoku::from_str(&format!("1{}", ".".repeat(80))).unwrap();
+        let grids = [s.clone(), s.clone()];
+        let each = s.display_with(&theme).to_string();
+        assert_eq!(
+            Sudoku::display_beside(&grids, &theme, 0).to_string(),
+            format!("{0}\n\n{0}", each)
+        );
+        assert_eq!(
+            Sudoku::display_beside(&grids, &theme, 51).to_string(),
+            Sudoku::display_beside(&grids, &theme, 0).to_string()
+        );
+        assert_eq!(
+            Sudoku::display_beside(&grids, &theme, 52)
+                .to_string()
+                .lines()
+                .count(),
+            13
+        );
+        assert_eq!(Sudoku::display_beside(&[], &theme, 80).to_string(), "");
+
+        // A shorter grid beside a taller one is padded to its width on the extra lines.
+        let display = Sudoku::display_beside(&[s, jigsaw(&".".repeat(81))], &theme, 80).to_string();
+        let lines: Vec<&str> = display.lines().collect();
+        assert_eq!(lines.len(), 19);
+        assert!(lines[18].starts_with(&" ".repeat(27)));
+    }
+
+    #[test]
+    fn display_unicode() {
+        let theme = Theme::new().color(false).border(Border::Unicode);
+        let s = Sudoku::from_str(&format!("{}1", ".".repeat(80))).unwrap();
+        let display = s.display_with(&theme).to_string();
+        let lines: Vec<&str> = display.lines().collect();
+        assert_eq!(lines[0], format!("┌{0}┬{0}┬{0}┐", "─".repeat(7)));
+        assert_eq!(lines[1], "│ . . . │ . . . │ . . . │");
+        assert_eq!(lines[4], format!("├{0}┼{0}┼{0}┤", "─".repeat(7)));
+        assert_eq!(lines[12], format!("└{0}┴{0}┴{0}┘", "─".repeat(7)));
+        let ascii = s.display_with(&Theme::new().color(false)).to_string();
+        assert_eq!(
+            display
+                .replace(['┌', '┬', '┐', '├', '┼', '┤', '└', '┴', '┘'], "+")
+                .replace('─', "-")
+                .replace('│', "|"),
+            ascii
+        );
+
+        let display = jigsaw(&".".repeat(81)).display_with(&theme).to_string();
+        let lines: Vec<&str> = display.lines().collect();
+        assert_eq!(lines[0], "┌───────┬───────────────┬───────────┐");
+        assert_eq!(lines[2], "│       └───────┐       │           │");
+        assert_eq!(lines[4], "│           ┌───┘       │           │");
+        assert!(lines.iter().all(|line| visible_width(line) == 37));
+
+        let display = s.display_candidates_with(&theme).to_string();
+        let lines: Vec<&str> = display.lines().collect();
+        assert_eq!(lines[0], format!("┌{0}┬{0}┬{0}┐", "─".repeat(13)));
+        assert_eq!(lines[4], format!("│{0}│{0}│{0}│", " ".repeat(13)));
+        assert_eq!(lines[12], format!("├{0}┼{0}┼{0}┤", "─".repeat(13)));
+        assert!(lines.iter().all(|line| visible_width(line) == 43));
+    }
+
+    #[test]
+    fn display_plain() {
+        let s = Sudoku::from_str(&format!("{}1", ".".repeat(80)))
+            .unwrap()
+            .try_insert((0, 0), 2)
+            .unwrap();
+        let plain = Theme::new().color(false);
+        for display in [
+            s.display_with(&plain).to_string(),
+            s.display_labeled_with(&plain).to_string(),
+            s.display_candidates_with(&plain).to_string(),
+            s.display_heatmap_with(&plain).to_string(),
+        ] {
+            assert!(!display.contains('\x1b'), "{}", display);
+        }
+        assert!(s.to_string().contains('\x1b'));
+
+        let heatmap = s.display_heatmap_with(&plain).to_string();
+        assert!(heatmap.lines().nth(11).unwrap().ends_with(" 1 |"));
+        assert_eq!(
+            s.display_with(&plain).to_string().lines().nth(1),
+            Some("| 2 . . | . . . | . . . |")
+        );
+    }
+
+    #[test]
+    fn display_themed_views() {
+        let s = Sudoku::from_str(&".".repeat(81)).unwrap();
+        let theme = Theme::new().blank('_');
+        let candidates = s.display_candidates_with(&theme).to_string();
+        assert!(candidates.lines().nth(1).unwrap().starts_with("| 123 123"));
+        let s = Sudoku::from_str(&format!(".23456789{}", ".".repeat(72))).unwrap();
+        let candidates = s.display_candidates_with(&theme).to_string();
+        assert!(candidates.lines().nth(1).unwrap().starts_with("| 1__     "));
+        assert!(candidates.lines().nth(2).unwrap().starts_with("| ___  "));
+        let labeled = s.display_labeled_with(&theme).to_string();
+        assert_eq!(
+            labeled.lines().nth(3).unwrap(),
+            "2 | _ _ _ | _ _ _ | _ _ _ |"
+        );
+    }
+}
@@ -0,0 +1,466 @@
+//! Conversions between [`Sudoku`] and other text formats.
+
+use crate::{ParseError, Sudoku};
+use alloc::{
+    boxed::Box,
+    format,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+
+/// Alphabet of the URL-safe base64 used by [`Sudoku::to_base64`].
+const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+impl Sudoku {
+    /// Returns a [`Sudoku`] from CSV with one row per line, where empty fields and `0` are blanks. Fields may be separated by either commas or semicolons, and blank lines are ignored.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use newdoku::Sudoku;
+    /// use std::str::FromStr;
+    ///
+    /// let csv = "5;;;;;;;;\n".to_string() + &";;;;;;;;\n".repeat(8);
+    /// assert_eq!(
+    ///     Sudoku::from_csv(&csv).unwrap(),
+    ///     Sudoku::from_str(&format!("5{}", ".".repeat(80))).unwrap()
+    /// );
+    /// ```
+    pub fn from_csv(src: &str) -> Result<Self, ParseError> {
+        let delimiter = if src.contains(';') { ';' } else { ',' };
+        let rows = src
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .enumerate()
+            .map(|(y, line)| {
+                line.split(delimiter)
+                    .map(str::trim)
+                    .enumerate()
+                    .map(|(x, field)| match field {
+                        "" => Ok(0),
+                        _ => field.parse().map_err(|_| ParseError::InvalidField {
+                            loc: (x, y),
+                            field: field.to_string(),
+                        }),
+                    })
+                    .collect()
+            })
+            .collect::<Result<Vec<Vec<u8>>, ParseError>>()?;
+        Self::from_rows(rows)
+    }
+
+    /// Returns `self` as comma-separated values with one row per line, leaving blank cells as empty fields.
+    pub fn to_csv(&self) -> String {
+        self.xs
+            .chunks(9)
+            .map(|row| {
+                let fields: Vec<String> = row
+                    .iter()
+                    .map(|x| x.map_or(String::new(), |num| num.to_string()))
+                    .collect();
+                fields.join(",") + "\n"
+            })
+            .collect()
+    }
+
+    /// Returns a [`Sudoku`] from exactly 81 bytes read left to right and then top to bottom, where `0` is a blank and `1..=9` are givens.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use newdoku::{ParseError, Sudoku};
+    ///
+    /// let mut bytes = [0; 81];
+    /// bytes[0] = 5;
+    /// let s = Sudoku::from_bytes(&bytes).unwrap();
+    /// assert_eq!(s.to_bytes(), bytes);
+    /// assert_eq!(Sudoku::from_bytes(&bytes[1..]), Err(ParseError::InvalidLength(80)));
+    /// ```
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ParseError> {
+        if bytes.len() != 81 {
+            return Err(ParseError::InvalidLength(bytes.len()));
+        }
+        Self::from_rows(bytes.chunks(9).map(<[u8]>::to_vec).collect())
+    }
+
+    /// Returns the cells of `self` as bytes in the format read by [`Sudoku::from_bytes`].
+    pub fn to_bytes(&self) -> [u8; 81] {
+        core::array::from_fn(|i| self.xs[i].map_or(0, |num| num.value()))
+    }
+
+    /// Returns `self` as 55 characters of unpadded URL-safe base64, suitable for a short puzzle id in a URL. Each cell takes four bits, holding `0` for a blank and the number otherwise, so edits are kept but not told apart from givens.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use newdoku::Sudoku;
+    /// use std::str::FromStr;
+    ///
+    /// let s = Sudoku::from_str(&format!("12{}", ".".repeat(79))).unwrap();
+    /// let id = s.to_base64();
+    /// assert_eq!(id.len(), 55);
+    /// assert!(id.starts_with("EgAA"));
+    /// assert_eq!(Sudoku::from_base64(&id).unwrap(), s);
+    /// ```
+    pub fn to_base64(&self) -> String {
+        self.to_nibbles()
+            .chunks(3)
+            .flat_map(|chunk| {
+                let bits = chunk.iter().enumerate().fold(0u32, |bits, (i, &byte)| {
+                    bits | (byte as u32) << (16 - 8 * i)
+                });
+                (0..=chunk.len())
+                    .map(move |i| BASE64[(bits >> (18 - 6 * i) & 0x3f) as usize] as char)
+            })
+            .collect()
+    }
+
+    /// Returns a [`Sudoku`] from an id produced by [`Sudoku::to_base64`], with every number read as a given.
+    ///
+    /// Returns [`ParseError::InvalidEncoding`] if `src` is not 55 characters of URL-safe base64, and [`ParseError::InvalidValue`] if a cell holds a value above 9.
+    pub fn from_base64(src: &str) -> Result<Self, ParseError> {
+        if src.len() != 55 {
+            return Err(ParseError::InvalidEncoding);
+        }
+        let sextets = src
+            .bytes()
+            .map(|c| BASE64.iter().position(|&b| b == c).map(|i| i as u32))
+            .collect::<Option<Vec<u32>>>()
+            .ok_or(ParseError::InvalidEncoding)?;
+        let bytes: Vec<u8> = sextets
+            .chunks(4)
+            .flat_map(|chunk| {
+                let bits = chunk
+                    .iter()
+                    .enumerate()
+                    .fold(0, |bits, (i, &sextet)| bits | sextet << (18 - 6 * i));
+                (0..chunk.len() - 1).map(move |i| (bits >> (16 - 8 * i)) as u8)
+            })
+            .collect();
+        if sextets[54] & 0x3 != 0 {
+            return Err(ParseError::InvalidEncoding);
+        }
+        Self::from_nibbles(&bytes)
+    }
+
+    /// Returns the cells of `self` packed two to a byte, four bits each with the first cell in the high bits, holding `0` for a blank and the number otherwise. The low bits of the last byte are unused and zero.
+    pub(crate) fn to_nibbles(&self) -> [u8; 41] {
+        let cells = self.to_bytes();
+        core::array::from_fn(|i| cells[2 * i] << 4 | cells.get(2 * i + 1).copied().unwrap_or(0))
+    }
+
+    /// Returns a [`Sudoku`] from cells packed by [`Sudoku::to_nibbles`], with every number read as a given.
+    ///
+    /// Returns [`ParseError::InvalidEncoding`] if `bytes` is not 41 bytes long or the unused bits are set, and [`ParseError::InvalidValue`] if a cell holds a value above 9.
+    pub(crate) fn from_nibbles(bytes: &[u8]) -> Result<Self, ParseError> {
+        // The final byte holds only the last cell, leaving its low bits unused.
+        if bytes.len() != 41 || bytes[40] & 0xf != 0 {
+            return Err(ParseError::InvalidEncoding);
+        }
+        let cells: Vec<u8> = bytes
+            .iter()
+            .flat_map(|&byte| [byte >> 4, byte & 0xf])
+            .take(81)
+            .collect();
+        Self::from_bytes(&cells)
+    }
+
+    /// Returns every puzzle of an OpenSudoku XML collection, read from the `data` attribute of each `<game>` element in order, along with an error for each entry which was skipped.
+    ///
+    /// The XML is scanned for `<game` tags rather than fully parsed. An entry which is missing its data or cannot be parsed with [`Sudoku::from_str_validated`] is left out of the puzzles and reported as [`ParseError::Line`] with the line its tag starts on, so one malformed entry does not prevent the rest of a pack from being imported.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use newdoku::Sudoku;
+    ///
+    /// let xml = format!(
+    ///     "<opensudoku>\n  <game data=\"{}\" />\n  <game data=\"123\" />\n</opensudoku>",
+    ///     "0".repeat(81)
+    /// );
+    /// let (sudokus, errors) = Sudoku::from_opensudoku_xml(&xml);
+    /// assert_eq!(sudokus.len(), 1);
+    /// assert_eq!(errors[0].to_string(), "line 3: expected 81 cells but found 3");
+    /// ```
+    pub fn from_opensudoku_xml(src: &str) -> (Vec<Self>, Vec<ParseError>) {
+        let mut sudokus = Vec::new();
+        let mut errors = Vec::new();
+        let mut rest = src;
+        // Line of the start of `rest`, counted from 1.
+        let mut line = 1;
+        while let Some(start) = rest.find("<game") {
+            line += rest[..start].matches('\n').count();
+            let tag = &rest[start..];
+            let tag = &tag[..tag.find('>').unwrap_or(tag.len())];
+            rest = &rest[start + tag.len()..];
+            let tag_line = line;
+            line += tag.matches('\n').count();
+            // Skip elements such as `<games>` which only share the prefix.
+            if tag[5..].starts_with(|c: char| c.is_alphanumeric() || c == '_' || c == '-') {
+                continue;
+            }
+
+            let data = tag
+                .split_once("data=")
+                .and_then(|(_, value)| {
+                    let quote = value.chars().next().filter(|&c| c == '"' || c == '\'')?;
+                    value[1..].split(quote).next()
+                })
+                .unwrap_or("");
+            match Self::from_str_validated(data) {
+                Ok(sudoku) => sudokus.push(sudoku),
+                Err(e) => errors.push(ParseError::Line {
+                    line: tag_line,
+                    source: Box::new(e),
+                }),
+            }
+        }
+        (sudokus, errors)
+    }
+
+    /// Returns `self` as a GitHub-flavored markdown table with the column numbers as its header and blanks as empty cells. Rows of `—` separate the bands of blocks.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use newdoku::Sudoku;
+    /// use std::str::FromStr;
+    ///
+    /// let s = Sudoku::from_str(&format!("5{}", ".".repeat(80))).unwrap();
+    /// let table = s.to_markdown();
+    /// assert_eq!(table.lines().nth(2), Some("| 5 |   |   |   |   |   |   |   |   |"));
+    /// assert_eq!(table.lines().count(), 13);
+    /// ```
+    pub fn to_markdown(&self) -> String {
+        let row = |cells: Vec<String>| format!("| {} |\n", cells.join(" | "));
+        let mut table = row((1..10).map(|x| x.to_string()).collect());
+        table.push_str(&"|:-:".repeat(9));
+        table.push_str("|\n");
+        for (y, cells) in self.xs.chunks(9).enumerate() {
+            if y == 3 || y == 6 {
+                table.push_str(&row(vec![String::from("—"); 9]));
+            }
+            table.push_str(&row(cells
+                .iter()
+                .map(|x| x.map_or(String::from(" "), |num| num.to_string()))
+                .collect()));
+        }
+        table
+    }
+
+    /// Returns `self` as nine lines of nine cells without borders, using `.` for blanks.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use newdoku::Sudoku;
+    /// use std::str::FromStr;
+    ///
+    /// let s = Sudoku::from_str(&format!("5{}", ".".repeat(80))).unwrap();
+    /// assert_eq!(s.line_with_boxes(), "5........\n".to_string() + &".........\n".repeat(8));
+    /// ```
+    pub fn line_with_boxes(&self) -> String {
+        let line = self.to_line();
+        (0..9)
+            .map(|y| format!("{}\n", &line[y * 9..y * 9 + 9]))
+            .collect()
+    }
+
+    /// Returns a [`Sudoku`] from nine lines of nine cells as produced by [`Sudoku::line_with_boxes`], where `.` and `0` are blanks. Surrounding whitespace and blank lines are ignored.
+    pub fn from_line_with_boxes(src: &str) -> Result<Self, ParseError> {
+        let rows = src
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .enumerate()
+            .map(|(y, line)| {
+                line.chars()
+                    .enumerate()
+                    .map(|(x, c)| match c {
+                        '.' => Ok(0),
+                        _ => c.to_digit(10).map(|num| num as u8).ok_or_else(|| {
+                            ParseError::InvalidField {
+                                loc: (x, y),
+                                field: c.to_string(),
+                            }
+                        }),
+                    })
+                    .collect()
+            })
+            .collect::<Result<Vec<Vec<u8>>, ParseError>>()?;
+        Self::from_rows(rows)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::str::FromStr;
+    const TEST_SUDOKU: &str =
+        "xxxxxxx9xx9x7xx21xxx4x9xxxxx1xxx8xxx7xx42xxx5xx8xxxx748x1xxxx4xxxxxxxxxxxx9613xxx";
+
+    #[test]
+    fn csv_round_trip() {
+        let s = Sudoku::from_str(TEST_SUDOKU).unwrap();
+        let csv = s.to_csv();
+        assert_eq!(csv.lines().next(), Some(",,,,,,,9,"));
+        assert_eq!(Sudoku::from_csv(&csv).unwrap(), s);
+        assert_eq!(Sudoku::from_csv(&csv.replace(',', ";")).unwrap(), s);
+    }
+
+    #[test]
+    fn line_with_boxes_round_trip() {
+        let s = Sudoku::from_str(TEST_SUDOKU).unwrap();
+        let lines = s.line_with_boxes();
+        assert_eq!(lines.lines().count(), 9);
+        assert_eq!(lines.lines().next(), Some(".......9."));
+        assert_eq!(Sudoku::from_line_with_boxes(&lines).unwrap(), s);
+        assert_eq!(
+            Sudoku::from_line_with_boxes(&lines.replace('.', "0")).unwrap(),
+            s
+        );
+        assert_eq!(
+            Sudoku::from_line_with_boxes(&lines.replacen(".", "", 1)),
+            Err(ParseError::InvalidRowLength { row: 0, len: 8 })
+        );
+        assert_eq!(
+            Sudoku::from_line_with_boxes(&lines.replacen('.', "x", 1)),
+            Err(ParseError::InvalidField {
+                loc: (0, 0),
+                field: String::from("x")
+            })
+        );
+    }
+
+    #[test]
+    fn bytes_round_trip() {
+        let s = Sudoku::from_str(TEST_SUDOKU).unwrap();
+        let bytes = s.to_bytes();
+        assert_eq!(bytes[7], 9);
+        assert_eq!(bytes[0], 0);
+        assert_eq!(Sudoku::from_bytes(&bytes).unwrap(), s);
+
+        let mut bytes = bytes.to_vec();
+        bytes[10] = 10;
+        assert_eq!(
+            Sudoku::from_bytes(&bytes),
+            Err(ParseError::InvalidValue {
+                loc: (1, 1),
+                value: 10
+            })
+        );
+        bytes.push(0);
+        assert_eq!(
+            Sudoku::from_bytes(&bytes),
+            Err(ParseError::InvalidLength(82))
+        );
+    }
+
+    #[test]
+    fn base64_round_trip() {
+        let s = Sudoku::from_str(TEST_SUDOKU).unwrap();
+        let id = s.to_base64();
+        assert_eq!(id.len(), 55);
+        assert!(id.bytes().all(|c| BASE64.contains(&c)));
+        assert_eq!(Sudoku::from_base64(&id).unwrap(), s);
+        let solved = s.solve_with(crate::Strategy::Propagate).unwrap();
+        assert_eq!(Sudoku::from_base64(&solved.to_base64()).unwrap(), solved);
+        assert_eq!(
+            Sudoku::from_base64(&Sudoku::from_str(&".".repeat(81)).unwrap().to_base64())
+                .unwrap()
+                .to_line(),
+            ".".repeat(81)
+        );
+
+        assert_eq!(
+            Sudoku::from_base64(&id[1..]),
+            Err(ParseError::InvalidEncoding)
+        );
+        assert_eq!(
+            Sudoku::from_base64(&id.replacen('A', "+", 1)),
+            Err(ParseError::InvalidEncoding)
+        );
+        let unused = format!("{}B", &id[..54]);
+        assert_eq!(
+            Sudoku::from_base64(&unused),
+            Err(ParseError::InvalidEncoding)
+        );
+        let ten = format!("o{}", &id[1..]);
+        assert_eq!(
+            Sudoku::from_base64(&ten),
+            Err(ParseError::InvalidValue {
+                loc: (0, 0),
+                value: 10
+            })
+        );
+    }
+
+    #[test]
+    fn to_markdown() {
+        let table = Sudoku::from_str(TEST_SUDOKU).unwrap().to_markdown();
+        let lines: Vec<&str> = table.lines().collect();
+        assert_eq!(lines.len(), 13);
+        assert_eq!(lines[0], "| 1 | 2 | 3 | 4 | 5 | 6 | 7 | 8 | 9 |");
+        assert_eq!(lines[1], "|:-:|:-:|:-:|:-:|:-:|:-:|:-:|:-:|:-:|");
+        assert_eq!(lines[2], "|   |   |   |   |   |   |   | 9 |   |");
+        assert_eq!(lines[5], "| — | — | — | — | — | — | — | — | — |");
+        assert_eq!(lines[9], lines[5]);
+        assert_eq!(lines[12], "|   |   | 9 | 6 | 1 | 3 |   |   |   |");
+        assert!(table.ends_with("|\n"));
+    }
+
+    #[test]
+    fn opensudoku_xml() {
+        let data = TEST_SUDOKU.replace('x', "0");
+        let xml = format!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<opensudoku>\n<name>Pack</name>\n<game data=\"{0}\"/>\n<game created=\"0\" data='{0}'>\n</game>\n</opensudoku>\n",
+            data
+        );
+        let (sudokus, errors) = Sudoku::from_opensudoku_xml(&xml);
+        assert_eq!(sudokus, vec![Sudoku::from_str(TEST_SUDOKU).unwrap(); 2]);
+        assert!(errors.is_empty());
+        assert_eq!(
+            Sudoku::from_opensudoku_xml("<games></games>"),
+            (Vec::new(), Vec::new())
+        );
+
+        // Malformed entries are skipped, and the entries after them still read.
+        let xml = format!(
+            "<opensudoku>\n<game data=\"{0}\"/>\n<game/>\n<game\n  data=\"11{1}\"/>\n<game data=\"{0}\"/>\n</opensudoku>",
+            data,
+            ".".repeat(79)
+        );
+        let (sudokus, errors) = Sudoku::from_opensudoku_xml(&xml);
+        assert_eq!(sudokus.len(), 2);
+        assert_eq!(
+            errors,
+            vec![
+                ParseError::Line {
+                    line: 3,
+                    source: Box::new(ParseError::InvalidLength(0))
+                },
+                ParseError::Line {
+                    line: 4,
+                    source: Box::new(ParseError::Conflict(crate::Unit::Row(0)))
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn csv_invalid() {
+        let csv = Sudoku::from_str(TEST_SUDOKU).unwrap().to_csv();
+        assert_eq!(
+            Sudoku::from_csv(&csv.replacen(",,", ",", 1)),
+            Err(ParseError::InvalidRowLength { row: 0, len: 8 })
+        );
+        assert_eq!(
+            Sudoku::from_csv(&csv.replacen(",9,", ",x,", 1)),
+            Err(ParseError::InvalidField {
+                loc: (7, 0),
+                field: String::from("x")
+            })
+        );
+    }
+}
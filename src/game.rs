@@ -0,0 +1,800 @@
+use crate::{solver, InsertError, ParseError, SolutionError, Sudoku, SudokuNum};
+use alloc::{
+    boxed::Box,
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::fmt;
+
+/// A change to a cell of a [`SudokuGame`], passed to the callbacks registered with [`SudokuGame::on_change`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CellEvent {
+    /// `num` was placed at `loc: (x, y)`, replacing any previous entry.
+    Placed { loc: (usize, usize), num: u8 },
+    /// The entry at `loc: (x, y)` was removed.
+    Cleared { loc: (usize, usize) },
+    /// `num` could not be placed at `loc: (x, y)` for the reason held in `error`.
+    Conflict {
+        loc: (usize, usize),
+        num: u8,
+        error: InsertError,
+    },
+}
+
+/// A puzzle being played, paired with its solution so that the player's entries can be checked against it.
+pub struct SudokuGame {
+    board: Sudoku,
+    solution: Sudoku,
+    /// Boards before each change to `board`, with the most recent last.
+    history: Vec<Sudoku>,
+    /// Pencil marks of each cell, with bit `n - 1` set for each marked number `n`.
+    marks: [u16; 81],
+    /// Callbacks registered with [`SudokuGame::on_change`], in the order they were registered.
+    listeners: Vec<Box<dyn FnMut(CellEvent)>>,
+}
+
+impl fmt::Debug for SudokuGame {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("SudokuGame")
+            .field("board", &self.board)
+            .field("solution", &self.solution)
+            .field("history", &self.history)
+            .field("marks", &self.marks)
+            .field("listeners", &self.listeners.len())
+            .finish()
+    }
+}
+
+impl Clone for SudokuGame {
+    /// Returns a copy of the game and its history without any of the callbacks registered with [`SudokuGame::on_change`].
+    fn clone(&self) -> Self {
+        Self {
+            board: self.board.clone(),
+            solution: self.solution.clone(),
+            history: self.history.clone(),
+            marks: self.marks,
+            listeners: Vec::new(),
+        }
+    }
+}
+
+impl SudokuGame {
+    /// Returns a game for `puzzle` checked against `solution`, which is trusted to be the intended answer without solving `puzzle` again.
+    ///
+    /// Returns [`SolutionError::NotFull`] if `solution` has empty cells, [`SolutionError::GivenMismatch`] if it differs from a given of `puzzle`, and [`SolutionError::Invalid`] if it breaks sudoku rules or the variant constraints of `puzzle`. Entries in `puzzle` which are not givens are not compared, so they may already be mistakes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use newdoku::{SolutionError, Sudoku, SudokuGame};
+    /// use std::str::FromStr;
+    ///
+    /// let solution = Sudoku::from_str(
+    ///     "157832496396745218284196753415378962763429185928561374831257649672984531549613827",
+    /// ).unwrap();
+    /// let puzzle = Sudoku::from_str(&format!("1{}", ".".repeat(80))).unwrap();
+    /// let game = SudokuGame::new(puzzle, solution.clone()).unwrap();
+    /// assert_eq!(game.solution(), &solution);
+    ///
+    /// let puzzle = Sudoku::from_str(&format!("2{}", ".".repeat(80))).unwrap();
+    /// assert_eq!(
+    ///     SudokuGame::new(puzzle, solution).unwrap_err(),
+    ///     SolutionError::GivenMismatch { loc: (0, 0) }
+    /// );
+    /// ```
+    pub fn new(puzzle: Sudoku, solution: Sudoku) -> Result<Self, SolutionError> {
+        if !solution.is_full() {
+            return Err(SolutionError::NotFull);
+        }
+        let mismatch = (0..81).find(|&idx| match puzzle.xs[idx] {
+            Some(SudokuNum::Original(num)) => solution.xs[idx] != Some(SudokuNum::Original(num)),
+            _ => false,
+        });
+        if let Some(idx) = mismatch {
+            return Err(SolutionError::GivenMismatch {
+                loc: (idx % 9, idx / 9),
+            });
+        }
+        // Checked with the constraints of `puzzle`, which `solution` may not carry.
+        let mut checked = puzzle.clone();
+        checked.xs = solution.xs;
+        if !checked.is_valid() {
+            return Err(SolutionError::Invalid);
+        }
+
+        Ok(Self {
+            board: puzzle,
+            solution: checked,
+            history: Vec::new(),
+            marks: [0; 81],
+            listeners: Vec::new(),
+        })
+    }
+
+    /// Returns a game for `puzzle` by solving it, or [`None`] if it has no solution. If `puzzle` has several solutions, the first one found is used.
+    pub fn from_puzzle(puzzle: Sudoku) -> Option<Self> {
+        let solution = solver::solve(&puzzle, &mut Default::default())?;
+        Some(Self {
+            board: puzzle,
+            solution,
+            history: Vec::new(),
+            marks: [0; 81],
+            listeners: Vec::new(),
+        })
+    }
+
+    /// Registers `callback` to be called with every change made to the board by [`SudokuGame::insert`], [`SudokuGame::clear`] and [`SudokuGame::undo`], and with every entry refused by [`SudokuGame::insert`].
+    ///
+    /// Callbacks are called in the order they were registered, after the board has changed. They are not kept by [`Clone`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use newdoku::{CellEvent, InsertError, Sudoku, SudokuGame};
+    /// use std::{cell::RefCell, rc::Rc, str::FromStr};
+    ///
+    /// let mut game = SudokuGame::from_puzzle(Sudoku::from_str(
+    ///     "xxxxxxx9xx9x7xx21xxx4x9xxxxx1xxx8xxx7xx42xxx5xx8xxxx748x1xxxx4xxxxxxxxxxxx9613xxx",
+    /// ).unwrap()).unwrap();
+    /// let events = Rc::new(RefCell::new(Vec::new()));
+    /// let log = Rc::clone(&events);
+    /// game.on_change(move |event| log.borrow_mut().push(event));
+    ///
+    /// game.insert((0, 0), 1).unwrap();
+    /// assert!(game.insert((1, 0), 9).is_err());
+    /// assert_eq!(
+    ///     *events.borrow(),
+    ///     [
+    ///         CellEvent::Placed { loc: (0, 0), num: 1 },
+    ///         CellEvent::Conflict { loc: (1, 0), num: 9, error: InsertError::RowDuplicate },
+    ///     ]
+    /// );
+    /// ```
+    pub fn on_change(&mut self, callback: impl FnMut(CellEvent) + 'static) {
+        self.listeners.push(Box::new(callback));
+    }
+
+    /// Calls every registered callback with `event`.
+    fn emit(&mut self, event: CellEvent) {
+        for listener in &mut self.listeners {
+            listener(event);
+        }
+    }
+
+    /// Returns the current board, including the player's entries.
+    pub fn board(&self) -> &Sudoku {
+        &self.board
+    }
+
+    /// Returns the solution the board is checked against.
+    pub fn solution(&self) -> &Sudoku {
+        &self.solution
+    }
+
+    /// Places `num` at `loc: (x, y)` if it can be inserted there by sudoku rules, replacing any previous entry. Givens cannot be replaced.
+    ///
+    /// The entry is accepted even if it contradicts the solution; see [`SudokuGame::is_mistake`].
+    pub fn insert(&mut self, loc: (usize, usize), num: u8) -> Result<(), InsertError> {
+        let result = self.try_place(loc, num);
+        let event = match result {
+            Ok(()) => CellEvent::Placed { loc, num },
+            Err(error) => CellEvent::Conflict { loc, num, error },
+        };
+        self.emit(event);
+        result
+    }
+
+    /// Places `num` at `loc` like [`SudokuGame::insert`], without calling the callbacks.
+    fn try_place(&mut self, loc: (usize, usize), num: u8) -> Result<(), InsertError> {
+        if loc.0 > 8 || loc.1 > 8 {
+            return Err(InsertError::InvalidLoc);
+        }
+        if let Some(SudokuNum::Original(_)) = self.board.xs[loc.1 * 9 + loc.0] {
+            return Err(InsertError::Occupied);
+        }
+
+        let mut board = self.board.clone();
+        board.xs[loc.1 * 9 + loc.0] = None;
+        let board = board.try_insert(loc, num)?;
+        self.history
+            .push(core::mem::replace(&mut self.board, board));
+        Ok(())
+    }
+
+    /// Removes the player's entry at `(x, y)`, if any. Givens are left in place.
+    ///
+    /// Returns [`InsertError::InvalidLoc`] if `x` or `y` is not within range 0..9.
+    pub fn clear(&mut self, x: usize, y: usize) -> Result<(), InsertError> {
+        if x > 8 || y > 8 {
+            return Err(InsertError::InvalidLoc);
+        }
+        if let Some(SudokuNum::Edited(_)) = self.board.xs[y * 9 + x] {
+            self.history.push(self.board.clone());
+            self.board.xs[y * 9 + x] = None;
+            self.emit(CellEvent::Cleared { loc: (x, y) });
+        }
+        Ok(())
+    }
+
+    /// Reverts the most recent entry or removal made with [`SudokuGame::insert`] or [`SudokuGame::clear`]. Returns false if there is nothing to undo.
+    pub fn undo(&mut self) -> bool {
+        let board = match self.history.pop() {
+            Some(board) => core::mem::replace(&mut self.board, board),
+            None => return false,
+        };
+        for idx in 0..81 {
+            let loc = (idx % 9, idx / 9);
+            let event = match self.board.xs[idx] {
+                x if x == board.xs[idx] => continue,
+                Some(num) => CellEvent::Placed {
+                    loc,
+                    num: num.value(),
+                },
+                None => CellEvent::Cleared { loc },
+            };
+            self.emit(event);
+        }
+        true
+    }
+
+    /// Adds `num` to the pencil marks of the empty cell at `loc: (x, y)`, or removes it if it is already marked. Marks are notes for the player, so they are neither checked against sudoku rules nor undone by [`SudokuGame::undo`].
+    ///
+    /// Returns [`InsertError::Occupied`] if the cell is filled.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use newdoku::{Sudoku, SudokuGame};
+    /// use std::str::FromStr;
+    ///
+    /// let mut game = SudokuGame::from_puzzle(Sudoku::from_str(&".".repeat(81)).unwrap()).unwrap();
+    /// game.toggle_mark((0, 0), 3).unwrap();
+    /// game.toggle_mark((0, 0), 1).unwrap();
+    /// assert_eq!(game.marks(0, 0), Some(vec![1, 3]));
+    /// game.toggle_mark((0, 0), 3).unwrap();
+    /// assert_eq!(game.marks(0, 0), Some(vec![1]));
+    /// ```
+    pub fn toggle_mark(&mut self, loc: (usize, usize), num: u8) -> Result<(), InsertError> {
+        if loc.0 > 8 || loc.1 > 8 {
+            return Err(InsertError::InvalidLoc);
+        }
+        if !(1..=9).contains(&num) {
+            return Err(InsertError::InvalidNumber);
+        }
+        let idx = loc.1 * 9 + loc.0;
+        if self.board.xs[idx].is_some() {
+            return Err(InsertError::Occupied);
+        }
+        self.marks[idx] ^= 1 << (num - 1);
+        Ok(())
+    }
+
+    /// Returns the pencil marks of the cell at `(x, y)` in ascending order, or [`None`] if `x` or `y` is not within range 0..9.
+    pub fn marks(&self, x: usize, y: usize) -> Option<Vec<u8>> {
+        if x > 8 || y > 8 {
+            return None;
+        }
+        let mask = self.marks[y * 9 + x];
+        Some((1..10).filter(|num| mask & 1 << (num - 1) != 0).collect())
+    }
+
+    /// Returns the whole state of the game as text which [`SudokuGame::load`] restores: the givens, the player's entries, the pencil marks, the undo history and the solution.
+    ///
+    /// The format is stable. It is a header line followed by one line per field, each starting with its keyword:
+    ///
+    /// ```text
+    /// newdoku-game 1
+    /// board <cells>
+    /// solution <cells>
+    /// marks <index>:<numbers> ...
+    /// history <cells>
+    /// ```
+    ///
+    /// Each `<cells>` holds 81 characters in reading order: `.` for an empty cell, `1` to `9` for a given and `a` to `i` for an entry of 1 to 9. `marks` lists each cell with pencil marks as its index `y * 9 + x` and its marked numbers in ascending order, separated by spaces. There is one `history` line for each change which can be undone, oldest first, holding the board before that change. Variant constraints and callbacks are not saved.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use newdoku::{Sudoku, SudokuGame};
+    /// use std::str::FromStr;
+    ///
+    /// let mut game = SudokuGame::from_puzzle(Sudoku::from_str(&format!("1{}", ".".repeat(80))).unwrap()).unwrap();
+    /// game.insert((1, 0), 2).unwrap();
+    /// game.toggle_mark((2, 0), 3).unwrap();
+    ///
+    /// let save = game.save();
+    /// let lines: Vec<&str> = save.lines().collect();
+    /// assert_eq!(lines[0], "newdoku-game 1");
+    /// assert!(lines[1].starts_with("board 1b......."));
+    /// assert_eq!(lines[3], "marks 2:3");
+    /// assert!(lines[4].starts_with("history 1......."));
+    ///
+    /// let mut loaded = SudokuGame::load(&save).unwrap();
+    /// assert_eq!(loaded.board(), game.board());
+    /// assert_eq!(loaded.marks(2, 0), Some(vec![3]));
+    /// assert!(loaded.undo());
+    /// ```
+    pub fn save(&self) -> String {
+        let marks: String = (0..81)
+            .filter(|&idx| self.marks[idx] != 0)
+            .map(|idx| {
+                let nums: String = (1..10)
+                    .filter(|num| self.marks[idx] & 1 << (num - 1) != 0)
+                    .map(|num| num.to_string())
+                    .collect();
+                format!(" {}:{}", idx, nums)
+            })
+            .collect();
+        let mut save = format!(
+            "{}\nboard {}\nsolution {}\nmarks{}\n",
+            SAVE_HEADER,
+            encode_cells(&self.board),
+            encode_cells(&self.solution),
+            marks
+        );
+        for board in &self.history {
+            save.push_str(&format!("history {}\n", encode_cells(board)));
+        }
+        save
+    }
+
+    /// Restores a game saved with [`SudokuGame::save`].
+    ///
+    /// Errors are wrapped in [`ParseError::Line`] with the line they were found on. A missing or unexpected line gives [`ParseError::InvalidEncoding`], as does a solution which is not a full valid grid or marks which cannot be read. A board of the wrong length gives [`ParseError::InvalidLength`], an unknown cell character [`ParseError::InvalidField`] and a board breaking sudoku rules [`ParseError::Conflict`].
+    pub fn load(src: &str) -> Result<Self, ParseError> {
+        let lines: Vec<&str> = src.lines().collect();
+        let at = |i: usize| {
+            move |e| ParseError::Line {
+                line: i + 1,
+                source: Box::new(e),
+            }
+        };
+        // Returns the rest of line `i`, counted from 0, after `keyword`.
+        let field =
+            |i: usize, keyword: &str| match lines.get(i).and_then(|l| l.strip_prefix(keyword)) {
+                Some(rest) if rest.is_empty() || rest.starts_with(' ') => Ok(rest.trim()),
+                _ => Err(at(i)(ParseError::InvalidEncoding)),
+            };
+
+        field(0, SAVE_HEADER)?;
+        let board = decode_cells(field(1, "board")?).map_err(at(1))?;
+        let solution = decode_cells(field(2, "solution")?).map_err(at(2))?;
+        if !solution.is_full() {
+            return Err(at(2)(ParseError::InvalidEncoding));
+        }
+
+        let mut marks = [0; 81];
+        for entry in field(3, "marks")?.split_whitespace() {
+            let (idx, nums) = entry
+                .split_once(':')
+                .and_then(|(idx, nums)| Some((idx.parse::<usize>().ok()?, nums)))
+                .filter(|&(idx, nums)| idx < 81 && !nums.is_empty())
+                .ok_or(at(3)(ParseError::InvalidEncoding))?;
+            for c in nums.chars() {
+                match c.to_digit(10) {
+                    Some(num @ 1..=9) => marks[idx] |= 1 << (num - 1),
+                    _ => return Err(at(3)(ParseError::InvalidEncoding)),
+                }
+            }
+        }
+
+        let history = (4..lines.len())
+            .map(|i| decode_cells(field(i, "history")?).map_err(at(i)))
+            .collect::<Result<Vec<Sudoku>, ParseError>>()?;
+        Ok(Self {
+            board,
+            solution,
+            history,
+            marks,
+            listeners: Vec::new(),
+        })
+    }
+
+    /// Returns true if every cell of the board is filled. Entries must follow sudoku rules, so a full board is always a valid solution of the puzzle.
+    pub fn is_solved(&self) -> bool {
+        self.board.is_full() && self.board.is_valid()
+    }
+
+    /// Returns whether the player's entry at `(x, y)` differs from the solution, even if it is allowed by sudoku rules. Givens and empty cells are never mistakes, and [`None`] is returned if `x` or `y` is not within range 0..9.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use newdoku::{Sudoku, SudokuGame};
+    /// use std::str::FromStr;
+    ///
+    /// let mut game = SudokuGame::from_puzzle(Sudoku::from_str(
+    ///     "xxxxxxx9xx9x7xx21xxx4x9xxxxx1xxx8xxx7xx42xxx5xx8xxxx748x1xxxx4xxxxxxxxxxxx9613xxx",
+    /// ).unwrap()).unwrap();
+    ///
+    /// game.insert((0, 0), 2).unwrap();
+    /// assert_eq!(game.is_mistake(0, 0), Some(true));
+    /// game.insert((0, 0), 1).unwrap();
+    /// assert_eq!(game.is_mistake(0, 0), Some(false));
+    /// assert_eq!(game.is_mistake(9, 0), None);
+    /// ```
+    pub fn is_mistake(&self, x: usize, y: usize) -> Option<bool> {
+        if x > 8 || y > 8 {
+            return None;
+        }
+        Some(match self.board.xs[y * 9 + x] {
+            Some(SudokuNum::Edited(num)) => {
+                self.solution.xs[y * 9 + x] != Some(SudokuNum::Edited(num))
+            }
+            _ => false,
+        })
+    }
+}
+
+/// First line of the text written by [`SudokuGame::save`], naming the version of the format.
+const SAVE_HEADER: &str = "newdoku-game 1";
+
+/// Returns the cells of `sudoku` in the format of [`SudokuGame::save`].
+fn encode_cells(sudoku: &Sudoku) -> String {
+    sudoku
+        .xs
+        .iter()
+        .map(|x| match x {
+            None => '.',
+            Some(SudokuNum::Original(num)) => char::from(b'0' + num),
+            Some(SudokuNum::Edited(num)) => char::from(b'a' + num - 1),
+        })
+        .collect()
+}
+
+/// Returns a [`Sudoku`] from cells in the format of [`SudokuGame::save`], checking that they follow sudoku rules.
+fn decode_cells(src: &str) -> Result<Sudoku, ParseError> {
+    let chars: Vec<char> = src.chars().collect();
+    if chars.len() != 81 {
+        return Err(ParseError::InvalidLength(chars.len()));
+    }
+    let mut xs = [None; 81];
+    for (idx, &c) in chars.iter().enumerate() {
+        xs[idx] = match c {
+            '.' => None,
+            '1'..='9' => Some(SudokuNum::Original(c as u8 - b'0')),
+            'a'..='i' => Some(SudokuNum::Edited(c as u8 - b'a' + 1)),
+            _ => {
+                return Err(ParseError::InvalidField {
+                    loc: (idx % 9, idx / 9),
+                    field: c.to_string(),
+                })
+            }
+        };
+    }
+    let sudoku = Sudoku::from_cells(xs);
+    match sudoku.find_conflict() {
+        Some(unit) => Err(ParseError::Conflict(unit)),
+        None => Ok(sudoku),
+    }
+}
+
+/// A completed grid hidden behind a blank board, so that it can be revealed one cell at a time. Returned by [`Sudoku::mask_all`].
+#[derive(Debug, Clone)]
+pub struct Reveal {
+    board: Sudoku,
+    solution: Sudoku,
+}
+
+impl Reveal {
+    /// Returns the board, holding only the cells revealed so far.
+    pub fn board(&self) -> &Sudoku {
+        &self.board
+    }
+
+    /// Returns the grid being revealed.
+    pub fn solution(&self) -> &Sudoku {
+        &self.solution
+    }
+
+    /// Copies the cell at `(x, y)` from the solution onto the board. Returns false if the cell was already revealed or is blank in the solution.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `x` or `y` is not within range 0..9.
+    pub fn reveal(&mut self, x: usize, y: usize) -> bool {
+        assert!(x < 9 && y < 9, "each coordinate must be within range 0..9");
+        let idx = y * 9 + x;
+        if self.board.xs[idx].is_some() || self.solution.xs[idx].is_none() {
+            return false;
+        }
+        self.board.xs[idx] = self.solution.xs[idx];
+        true
+    }
+
+    /// Returns true if every cell of the solution has been revealed.
+    pub fn is_revealed(&self) -> bool {
+        self.board == self.solution
+    }
+}
+
+impl Sudoku {
+    /// Returns a [`Reveal`] with every cell of `self` hidden, keeping `self` as the solution to reveal from.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use newdoku::Sudoku;
+    /// use std::str::FromStr;
+    ///
+    /// let s = Sudoku::from_str(
+    ///     "157832496396745218284196753415378962763429185928561374831257649672984531549613827",
+    /// ).unwrap();
+    /// let mut reveal = s.mask_all();
+    /// assert_eq!(reveal.board().to_line(), ".".repeat(81));
+    /// reveal.reveal(0, 0);
+    /// assert!(reveal.board().to_line().starts_with("1."));
+    /// ```
+    pub fn mask_all(&self) -> Reveal {
+        let mut board = self.clone();
+        board.xs = [None; 81];
+        Reveal {
+            board,
+            solution: self.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::str::FromStr;
+    const TEST_SUDOKU: &str =
+        "xxxxxxx9xx9x7xx21xxx4x9xxxxx1xxx8xxx7xx42xxx5xx8xxxx748x1xxxx4xxxxxxxxxxxx9613xxx";
+
+    #[test]
+    fn insert() {
+        let mut game = SudokuGame::from_puzzle(Sudoku::from_str(TEST_SUDOKU).unwrap()).unwrap();
+        assert_eq!(game.insert((7, 0), 1), Err(InsertError::Occupied));
+        assert_eq!(game.insert((0, 0), 9), Err(InsertError::RowDuplicate));
+
+        game.insert((0, 0), 3).unwrap();
+        game.insert((0, 0), 3).unwrap();
+        assert_eq!(game.is_mistake(0, 0), Some(true));
+        assert_eq!(game.is_mistake(7, 0), Some(false));
+
+        game.clear(0, 0).unwrap();
+        game.clear(7, 0).unwrap();
+        assert_eq!(game.is_mistake(0, 0), Some(false));
+        assert_eq!(game.board(), &Sudoku::from_str(TEST_SUDOKU).unwrap());
+    }
+
+    #[test]
+    fn out_of_range() {
+        let mut game = SudokuGame::from_puzzle(Sudoku::from_str(TEST_SUDOKU).unwrap()).unwrap();
+        game.insert((0, 1), 3).unwrap();
+        assert_eq!(game.clear(9, 0), Err(InsertError::InvalidLoc));
+        assert_eq!(game.clear(0, 9), Err(InsertError::InvalidLoc));
+        assert_eq!(game.is_mistake(9, 0), None);
+        assert_eq!(game.is_mistake(0, 9), None);
+        assert_eq!(game.board().to_line().as_bytes()[9], b'3');
+    }
+
+    #[test]
+    fn undo() {
+        let mut game = SudokuGame::from_puzzle(Sudoku::from_str(TEST_SUDOKU).unwrap()).unwrap();
+        assert!(!game.undo());
+        game.insert((0, 0), 3).unwrap();
+        game.insert((0, 0), 1).unwrap();
+        game.clear(0, 0).unwrap();
+        game.clear(0, 0).unwrap();
+        assert_eq!(game.insert((7, 0), 1), Err(InsertError::Occupied));
+
+        assert!(game.undo());
+        assert_eq!(game.board().to_line().as_bytes()[0], b'1');
+        assert!(game.undo());
+        assert_eq!(game.board().to_line().as_bytes()[0], b'3');
+        assert!(game.undo());
+        assert_eq!(game.board(), &Sudoku::from_str(TEST_SUDOKU).unwrap());
+        assert!(!game.undo());
+    }
+
+    #[test]
+    fn on_change() {
+        use alloc::rc::Rc;
+        use core::cell::RefCell;
+
+        let mut game = SudokuGame::from_puzzle(Sudoku::from_str(TEST_SUDOKU).unwrap()).unwrap();
+        let events = Rc::new(RefCell::new(Vec::new()));
+        let log = Rc::clone(&events);
+        game.on_change(move |event| log.borrow_mut().push(event));
+
+        game.insert((0, 0), 3).unwrap();
+        game.insert((0, 0), 1).unwrap();
+        game.clear(0, 0).unwrap();
+        game.clear(0, 0).unwrap();
+        assert_eq!(game.insert((9, 0), 1), Err(InsertError::InvalidLoc));
+        assert!(game.undo());
+        assert!(game.undo());
+        assert_eq!(
+            *events.borrow(),
+            [
+                CellEvent::Placed {
+                    loc: (0, 0),
+                    num: 3
+                },
+                CellEvent::Placed {
+                    loc: (0, 0),
+                    num: 1
+                },
+                CellEvent::Cleared { loc: (0, 0) },
+                CellEvent::Conflict {
+                    loc: (9, 0),
+                    num: 1,
+                    error: InsertError::InvalidLoc
+                },
+                CellEvent::Placed {
+                    loc: (0, 0),
+                    num: 1
+                },
+                CellEvent::Placed {
+                    loc: (0, 0),
+                    num: 3
+                },
+            ]
+        );
+
+        let mut copy = game.clone();
+        copy.clear(0, 0).unwrap();
+        assert_eq!(events.borrow().len(), 6);
+    }
+
+    #[test]
+    fn save_load() {
+        let mut game = SudokuGame::from_puzzle(Sudoku::from_str(TEST_SUDOKU).unwrap()).unwrap();
+        game.insert((0, 0), 3).unwrap();
+        game.insert((0, 0), 1).unwrap();
+        game.toggle_mark((1, 0), 5).unwrap();
+        game.toggle_mark((1, 0), 2).unwrap();
+        game.toggle_mark((8, 8), 9).unwrap();
+        assert_eq!(game.toggle_mark((0, 0), 2), Err(InsertError::Occupied));
+        assert_eq!(game.toggle_mark((1, 0), 0), Err(InsertError::InvalidNumber));
+        assert_eq!(game.marks(9, 0), None);
+
+        let save = game.save();
+        assert_eq!(save.lines().count(), 6);
+        assert_eq!(save.lines().nth(3), Some("marks 1:25 80:9"));
+        let mut loaded = SudokuGame::load(&save).unwrap();
+        assert_eq!(loaded.save(), save);
+        assert!(matches!(loaded.board.xs[0], Some(SudokuNum::Edited(1))));
+        assert!(matches!(loaded.board.xs[7], Some(SudokuNum::Original(9))));
+        assert_eq!(loaded.solution(), game.solution());
+        assert!(loaded.undo());
+        assert_eq!(loaded.board().to_line().as_bytes()[0], b'3');
+        assert!(loaded.undo());
+        assert!(!loaded.undo());
+
+        let fresh = SudokuGame::from_puzzle(Sudoku::from_str(TEST_SUDOKU).unwrap()).unwrap();
+        assert_eq!(
+            SudokuGame::load(&fresh.save()).unwrap().save(),
+            fresh.save()
+        );
+    }
+
+    #[test]
+    fn load_invalid() {
+        let game = SudokuGame::from_puzzle(Sudoku::from_str(TEST_SUDOKU).unwrap()).unwrap();
+        let save = game.save();
+        let at = |line, source| {
+            Err(ParseError::Line {
+                line,
+                source: Box::new(source),
+            })
+        };
+        assert_eq!(
+            SudokuGame::load(&save.replace("game 1", "game 2")).map(|_| ()),
+            at(1, ParseError::InvalidEncoding)
+        );
+        assert_eq!(
+            SudokuGame::load(&save.replacen(".", "", 1)).map(|_| ()),
+            at(2, ParseError::InvalidLength(80))
+        );
+        assert_eq!(
+            SudokuGame::load(&save.replacen(".", "x", 1)).map(|_| ()),
+            at(
+                2,
+                ParseError::InvalidField {
+                    loc: (0, 0),
+                    field: String::from("x")
+                }
+            )
+        );
+        assert_eq!(
+            SudokuGame::load(&save.replacen(".", "9", 1)).map(|_| ()),
+            at(2, ParseError::Conflict(crate::Unit::Row(0)))
+        );
+        let board = save.lines().nth(1).unwrap().replace("board", "solution");
+        assert_eq!(
+            SudokuGame::load(&save.replacen(save.lines().nth(2).unwrap(), &board, 1)).map(|_| ()),
+            at(3, ParseError::InvalidEncoding)
+        );
+        assert_eq!(
+            SudokuGame::load(&save.replace("marks", "marks 81:1")).map(|_| ()),
+            at(4, ParseError::InvalidEncoding)
+        );
+        assert_eq!(
+            SudokuGame::load(&save.replace("marks\n", "")).map(|_| ()),
+            at(4, ParseError::InvalidEncoding)
+        );
+        assert_eq!(
+            SudokuGame::load(&format!("{}history\n", save)).map(|_| ()),
+            at(5, ParseError::InvalidLength(0))
+        );
+        assert_eq!(
+            SudokuGame::load(&format!("{}done\n", save)).map(|_| ()),
+            at(5, ParseError::InvalidEncoding)
+        );
+    }
+
+    #[test]
+    fn new() {
+        let puzzle = Sudoku::from_str(TEST_SUDOKU).unwrap();
+        let solution = SudokuGame::from_puzzle(puzzle.clone())
+            .unwrap()
+            .solution()
+            .clone();
+        let game = SudokuGame::new(puzzle.clone(), solution.clone()).unwrap();
+        assert_eq!(game.board(), &puzzle);
+        assert_eq!(game.solution(), &solution);
+
+        let mut missing = solution.clone();
+        missing.xs[0] = None;
+        assert_eq!(
+            SudokuGame::new(puzzle.clone(), missing).unwrap_err(),
+            SolutionError::NotFull
+        );
+        let mut swapped = solution.clone();
+        swapped.xs.swap(0, 1);
+        assert_eq!(
+            SudokuGame::new(Sudoku::from_str(&".".repeat(81)).unwrap(), swapped.clone())
+                .unwrap_err(),
+            SolutionError::Invalid
+        );
+        assert_eq!(
+            SudokuGame::new(puzzle, swapped).unwrap_err(),
+            SolutionError::Invalid
+        );
+    }
+
+    #[test]
+    fn is_solved() {
+        let mut game = SudokuGame::from_puzzle(Sudoku::from_str(TEST_SUDOKU).unwrap()).unwrap();
+        let solution = game.solution().to_line();
+        for (idx, c) in solution.bytes().enumerate() {
+            assert!(!game.is_solved());
+            if game.board().xs[idx].is_none() {
+                game.insert((idx % 9, idx / 9), c - b'0').unwrap();
+            }
+        }
+        assert!(game.is_solved());
+    }
+
+    #[test]
+    fn reveal() {
+        let s = Sudoku::from_str(TEST_SUDOKU).unwrap();
+        let mut reveal = s.mask_all();
+        assert!(!reveal.is_revealed());
+        assert!(reveal.reveal(7, 0));
+        assert!(!reveal.reveal(7, 0));
+        assert!(!reveal.reveal(0, 0));
+        assert!(matches!(reveal.board().xs[7], Some(SudokuNum::Original(9))));
+
+        for i in 0..81 {
+            reveal.reveal(i % 9, i / 9);
+        }
+        assert!(reveal.is_revealed());
+        assert_eq!(reveal.board(), reveal.solution());
+    }
+
+    #[test]
+    fn unsolvable() {
+        assert!(SudokuGame::from_puzzle(
+            Sudoku::from_str(
+                "xxxxxxx9xx9x7xx21xxx4x9xxxxx1xxx8xxx7xx42xxx5xx8xxxx748x1xxxx4xxxxxxxxxxxx9613xx5",
+            )
+            .unwrap()
+        )
+        .is_none());
+    }
+}
@@ -0,0 +1,288 @@
+//! Randomised grid construction, available with the `rand` feature.
+
+#[cfg(feature = "std")]
+use crate::Theme;
+use crate::{solver, NoSolution, Sudoku, SudokuNum};
+use alloc::vec::Vec;
+use rand::{seq::SliceRandom, Rng};
+#[cfg(feature = "std")]
+use std::time::Duration;
+
+impl Sudoku {
+    /// Fills every empty cell of `self` to produce a complete, valid grid, trying candidates in an order chosen by `rng` so that different seeds give different completions.
+    ///
+    /// Returns [`NoSolution`] and leaves `self` unchanged if it cannot be completed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use newdoku::{rand::thread_rng, Sudoku};
+    /// use std::str::FromStr;
+    ///
+    /// let mut s = Sudoku::from_str(&".".repeat(81)).unwrap();
+    /// s.fill_random_valid(&mut thread_rng()).unwrap();
+    /// assert!(s.is_full() && s.is_valid());
+    /// ```
+    pub fn fill_random_valid<R: Rng + ?Sized>(&mut self, rng: &mut R) -> Result<(), NoSolution> {
+        let solution =
+            solver::solve_ordered(self, &mut Default::default(), &mut |nums| nums.shuffle(rng))
+                .ok_or(NoSolution)?;
+        *self = solution;
+        Ok(())
+    }
+
+    /// Returns a puzzle with exactly `clues` givens and a unique solution, or [`None`] if none was found within `attempts` attempts.
+    ///
+    /// Each attempt fills a random grid and removes givens in a random order for as long as the solution stays unique. Puzzles with few clues are rare, so low clue counts often fail, and counts below 17 always do.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use newdoku::{rand::thread_rng, Sudoku};
+    ///
+    /// let puzzle = Sudoku::generate_with_clues(40, 10, &mut thread_rng()).unwrap();
+    /// assert_eq!(puzzle.to_line().matches('.').count(), 41);
+    /// assert_eq!(puzzle.count_solutions(2), 1);
+    /// ```
+    pub fn generate_with_clues<R: Rng + ?Sized>(
+        clues: usize,
+        attempts: usize,
+        rng: &mut R,
+    ) -> Option<Self> {
+        if clues > 81 {
+            return None;
+        }
+
+        for _ in 0..attempts {
+            let mut grid = Self::from_cells([None; 81]);
+            grid.fill_random_valid(rng).ok()?;
+            for x in grid.xs.iter_mut().flatten() {
+                *x = SudokuNum::Original(x.value());
+            }
+
+            let mut order: Vec<usize> = (0..81).collect();
+            order.shuffle(rng);
+            let mut remaining = 81;
+            for idx in order {
+                if remaining == clues {
+                    break;
+                }
+                let value = grid.xs[idx].take();
+                if grid.count_solutions(2) == 1 {
+                    remaining -= 1;
+                } else {
+                    grid.xs[idx] = value;
+                }
+            }
+            if remaining == clues {
+                return Some(grid);
+            }
+        }
+        None
+    }
+
+    /// Returns a puzzle with a subset of the givens of `self` and the same unique solution, from which no more givens can be removed, as reported by [`Sudoku::is_minimal`]. Edits are discarded.
+    ///
+    /// Givens are tried for removal one at a time in an order chosen by `rng`, and each is removed if the solution stays unique. If the givens of `self` do not have a unique solution, they are returned unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use newdoku::{rand::thread_rng, Sudoku};
+    /// use std::str::FromStr;
+    ///
+    /// let solved = Sudoku::from_str(
+    ///     "157832496396745218284196753415378962763429185928561374831257649672984531549613827",
+    /// ).unwrap();
+    /// let puzzle = solved.minimize(&mut thread_rng());
+    /// assert!(puzzle.is_minimal());
+    /// assert_eq!(puzzle.solutions().next(), Some(solved));
+    /// ```
+    pub fn minimize<R: Rng + ?Sized>(&self, rng: &mut R) -> Self {
+        let mut puzzle = self.givens();
+        if puzzle.count_solutions(2) != 1 {
+            return puzzle;
+        }
+
+        let mut order: Vec<usize> = (0..81).filter(|&idx| puzzle.xs[idx].is_some()).collect();
+        order.shuffle(rng);
+        for idx in order {
+            let value = puzzle.xs[idx].take();
+            if puzzle.count_solutions(2) != 1 {
+                puzzle.xs[idx] = value;
+            }
+        }
+        puzzle
+    }
+
+    /// Applies a random combination of band, stack, row and column swaps, an optional transpose and a digit relabelling chosen by `rng`, giving a puzzle which looks different but is solved by exactly the same logic.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use newdoku::{rand::thread_rng, Sudoku};
+    /// use std::str::FromStr;
+    ///
+    /// let puzzle = Sudoku::from_str(
+    ///     "xxxxxxx9xx9x7xx21xxx4x9xxxxx1xxx8xxx7xx42xxx5xx8xxxx748x1xxxx4xxxxxxxxxxxx9613xxx",
+    /// ).unwrap();
+    /// let mut s = puzzle.clone();
+    /// s.shuffle(&mut thread_rng());
+    /// assert_eq!(s.signature(), puzzle.signature());
+    /// ```
+    pub fn shuffle<R: Rng + ?Sized>(&mut self, rng: &mut R) {
+        for i in 0..3 {
+            self.swap_bands(i, rng.gen_range(i..3));
+            self.swap_stacks(i, rng.gen_range(i..3));
+            for j in 0..3 {
+                self.swap_rows_in_band(i, j, rng.gen_range(j..3));
+                self.swap_cols_in_stack(i, j, rng.gen_range(j..3));
+            }
+        }
+        if rng.gen() {
+            self.transpose();
+        }
+
+        let mut map = [1, 2, 3, 4, 5, 6, 7, 8, 9];
+        map.shuffle(rng);
+        self.relabel(map);
+    }
+
+    /// Returns the solved [`Sudoku`] like [`Sudoku::solution_with`], animating every step with the default [`Theme`], but trying the numbers of each cell in an order chosen by `rng` so that different seeds give different animations. A puzzle with a unique solution always ends on the same grid.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use newdoku::{rand::thread_rng, Sudoku};
+    /// use std::{str::FromStr, time::Duration};
+    ///
+    /// let s = Sudoku::from_str(
+    ///     "...832496396745218284196753415378962763429185928561374831257649672984531549613...",
+    /// ).unwrap();
+    /// let solution = s.solve_animated_varied(Duration::ZERO, &mut thread_rng());
+    /// assert_eq!(solution, s.solution(0, true));
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn solve_animated_varied<R: Rng + ?Sized>(
+        &self,
+        step: Duration,
+        rng: &mut R,
+    ) -> Option<Self> {
+        self.solution_ordered(step, false, &Theme::default(), &mut |nums| {
+            nums.shuffle(rng)
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rand::{rngs::StdRng, SeedableRng};
+    use std::str::FromStr;
+
+    #[test]
+    fn fill_random_valid() {
+        let empty = Sudoku::from_str(&".".repeat(81)).unwrap();
+        let mut a = empty.clone();
+        let mut b = empty;
+        a.fill_random_valid(&mut StdRng::seed_from_u64(1)).unwrap();
+        b.fill_random_valid(&mut StdRng::seed_from_u64(2)).unwrap();
+        assert!(a.is_full() && a.is_valid());
+        assert!(b.is_full() && b.is_valid());
+        assert_ne!(a, b);
+    }
+
+    #[test]
+    fn fill_random_valid_keeps_givens() {
+        let puzzle = Sudoku::from_str(
+            "xxxxxxx9xx9x7xx21xxx4x9xxxxx1xxx8xxx7xx42xxx5xx8xxxx748x1xxxx4xxxxxxxxxxxx9613xxx",
+        )
+        .unwrap();
+        let mut s = puzzle.clone();
+        s.fill_random_valid(&mut StdRng::seed_from_u64(0)).unwrap();
+        assert_eq!(s.display_givens().to_string(), puzzle.to_string());
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn solution_ordered() {
+        let empty = Sudoku::from_str(&".".repeat(81)).unwrap();
+        let solve = |seed| {
+            let mut rng = StdRng::seed_from_u64(seed);
+            empty
+                .solution_ordered(Duration::ZERO, true, &Theme::default(), &mut |nums| {
+                    nums.shuffle(&mut rng)
+                })
+                .unwrap()
+        };
+        let (a, b) = (solve(1), solve(2));
+        assert!(a.is_full() && a.is_valid());
+        assert!(b.is_full() && b.is_valid());
+        assert_ne!(a, b);
+        assert_eq!(solve(1), a);
+    }
+
+    #[test]
+    fn generate_with_clues() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let puzzle = Sudoku::generate_with_clues(30, 5, &mut rng).unwrap();
+        assert_eq!(puzzle.to_line().matches('.').count(), 51);
+        assert_eq!(puzzle.count_solutions(2), 1);
+        assert_eq!(puzzle.display_givens().to_string(), puzzle.to_string());
+
+        assert!(Sudoku::generate_with_clues(81, 1, &mut rng)
+            .unwrap()
+            .is_full());
+        assert_eq!(Sudoku::generate_with_clues(12, 2, &mut rng), None);
+        assert_eq!(Sudoku::generate_with_clues(82, 1, &mut rng), None);
+        assert_eq!(Sudoku::generate_with_clues(30, 0, &mut rng), None);
+    }
+
+    #[test]
+    fn minimize() {
+        let puzzle = Sudoku::from_str(
+            "xxxxxxx9xx9x7xx21xxx4x9xxxxx1xxx8xxx7xx42xxx5xx8xxxx748x1xxxx4xxxxxxxxxxxx9613xxx",
+        )
+        .unwrap();
+        let solution = puzzle.solutions().next().unwrap();
+        let edited = puzzle.try_insert((0, 0), 1).unwrap();
+        let minimal = edited.minimize(&mut StdRng::seed_from_u64(0));
+        assert!(minimal.is_minimal());
+        assert!(minimal
+            .xs
+            .iter()
+            .all(|x| !matches!(x, Some(SudokuNum::Edited(_)))));
+        assert_eq!(minimal.solutions().next(), Some(solution));
+
+        let empty = Sudoku::from_str(&".".repeat(81)).unwrap();
+        assert_eq!(empty.minimize(&mut StdRng::seed_from_u64(0)), empty);
+    }
+
+    #[test]
+    fn shuffle() {
+        let puzzle = Sudoku::from_str(
+            "xxxxxxx9xx9x7xx21xxx4x9xxxxx1xxx8xxx7xx42xxx5xx8xxxx748x1xxxx4xxxxxxxxxxxx9613xxx",
+        )
+        .unwrap();
+        let mut s = puzzle.clone();
+        s.shuffle(&mut StdRng::seed_from_u64(0));
+        assert_ne!(s, puzzle);
+        assert_eq!(s.signature(), puzzle.signature());
+        assert_eq!(s.to_line().matches('.').count(), 58);
+        assert_eq!(s.solutions().count(), 1);
+    }
+
+    #[test]
+    fn fill_random_invalid() {
+        let mut s = Sudoku::from_str(
+            "xxxxxxx9xx9x7xx21xxx4x9xxxxx1xxx8xxx7xx42xxx5xx8xxxx748x1xxxx4xxxxxxxxxxxx9613xx5",
+        )
+        .unwrap();
+        let before = s.clone();
+        assert_eq!(
+            s.fill_random_valid(&mut StdRng::seed_from_u64(0)),
+            Err(NoSolution)
+        );
+        assert_eq!(s, before);
+    }
+}
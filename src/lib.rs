@@ -14,12 +14,55 @@
 //!
 //! println!("{}\n\n{}", s, s.solution(0, false).unwrap());
 //! ```
+//!
+//! # Features
+//!
+//! - `std` (default): terminal display and the animated [`Sudoku::solution`]. Without it the crate is `no_std` and needs only `alloc`, keeping parsing, validation, solving and the variant constraints.
+//! - `clap` (default): re-exports [`clap`] for the binary.
+//! - `rand`: randomised construction such as [`Sudoku::fill_random_valid`].
+
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+extern crate alloc;
 
 #[cfg(feature = "clap")]
 pub use clap;
+#[cfg(feature = "rand")]
+pub use rand;
+
+mod candidate;
+#[cfg(feature = "std")]
+mod display;
+mod format;
+mod game;
+#[cfg(feature = "rand")]
+mod generate;
+mod logic;
+#[cfg(feature = "std")]
+mod pack;
+mod solver;
+mod symbols;
+mod transform;
+mod variant;
+
+pub use candidate::CandidateGrid;
+#[cfg(feature = "std")]
+pub use display::{Border, Theme};
+pub use game::{CellEvent, Reveal, SudokuGame};
+pub use logic::{Elimination, StepDifficulty, TechniqueProfile};
+pub use solver::{SearchNode, SearchOutcome, Solutions, SolveOutcome, Strategy, UniqueResult};
+pub use symbols::SymbolSet;
+pub use transform::Symmetry;
+pub use variant::Parity;
+
+use variant::Variants;
 
-use crossterm::{cursor, style::Attribute};
-use std::{error::Error, fmt, str::FromStr, thread, time::Duration};
+use alloc::{boxed::Box, string::String, vec::Vec};
+use core::{error::Error, fmt, str::FromStr};
+#[cfg(feature = "std")]
+use crossterm::{cursor, terminal};
+#[cfg(feature = "std")]
+use std::{thread, time::Duration};
 
 #[derive(Debug, Clone, Copy)]
 enum SudokuNum {
@@ -43,6 +86,14 @@ impl PartialEq for SudokuNum {
     }
 }
 
+impl SudokuNum {
+    fn value(self) -> u8 {
+        match self {
+            Self::Original(num) | Self::Edited(num) => num,
+        }
+    }
+}
+
 impl fmt::Display for SudokuNum {
     fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
         match self {
@@ -52,27 +103,125 @@ impl fmt::Display for SudokuNum {
     }
 }
 
-/// Contains an 81-size array of [`Option<u8>`].
-#[derive(Debug, Clone, Copy)]
+/// Cell indices of each row, column and 3x3 block, in that order.
+const UNITS: [[usize; 9]; 27] = units();
+
+const fn units() -> [[usize; 9]; 27] {
+    let mut units = [[0; 9]; 27];
+    let mut i = 0;
+    while i < 9 {
+        let mut j = 0;
+        while j < 9 {
+            units[i][j] = i * 9 + j;
+            units[9 + i][j] = j * 9 + i;
+            units[18 + i][j] = (i / 3 * 3 + j / 3) * 9 + i % 3 * 3 + j % 3;
+            j += 1;
+        }
+        i += 1;
+    }
+    units
+}
+
+/// A group of cells which must contain distinct digits. Indices are 0-indexed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Unit {
+    /// A row, indexed from the top.
+    Row(usize),
+    /// A column, indexed from the left.
+    Col(usize),
+    /// A 3x3 block, indexed left to right and then top to bottom.
+    Block(usize),
+    /// An extra region, indexed in the order it was added with [`Sudoku::add_region`].
+    Region(usize),
+}
+
+impl fmt::Display for Unit {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        match self {
+            Self::Row(i) => write!(f, "row {}", i + 1),
+            Self::Col(i) => write!(f, "col {}", i + 1),
+            Self::Block(i) => write!(f, "block {}", i + 1),
+            Self::Region(i) => write!(f, "region {}", i + 1),
+        }
+    }
+}
+
+/// Contains an 81-size array of [`Option<u8>`], along with any variant constraints such as extra regions registered with [`Sudoku::add_region`].
+#[derive(Debug, Clone)]
 pub struct Sudoku {
     xs: [Option<SudokuNum>; 81],
+    variants: Variants,
+    /// Character used for blanks in the parsed input, kept so that [`Sudoku::to_line_with`] can reproduce it.
+    blank: char,
 }
 
-#[derive(Debug)]
-pub struct ParseError;
+#[derive(Debug, PartialEq)]
+pub enum ParseError {
+    /// The input did not contain exactly 81 cells. Holds the number of cells found.
+    InvalidLength(usize),
+    /// The givens already contain a duplicate in the unit.
+    Conflict(Unit),
+    /// The input did not contain exactly 9 rows. Holds the number of rows found.
+    InvalidRowCount(usize),
+    /// A row did not contain exactly 9 cells.
+    InvalidRowLength { row: usize, len: usize },
+    /// A cell value was not within range 0..=9.
+    InvalidValue { loc: (usize, usize), value: u8 },
+    /// A field could not be read as a cell value.
+    InvalidField { loc: (usize, usize), field: String },
+    /// Encoded input such as that read by [`Sudoku::from_base64`] could not be decoded.
+    InvalidEncoding,
+    /// A line of a multi-line input could not be parsed. Holds the line number, counted from 1, and the error for that line.
+    Line {
+        line: usize,
+        source: Box<ParseError>,
+    },
+}
 
-impl Error for ParseError {}
+impl Error for ParseError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Line { source, .. } => Some(source.as_ref()),
+            _ => None,
+        }
+    }
+}
 
 impl fmt::Display for ParseError {
     fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
-        write!(f, "could not parse input as sudoku")
+        match self {
+            Self::InvalidLength(len) => write!(f, "expected 81 cells but found {}", len),
+            Self::Conflict(unit) => write!(f, "givens contain a duplicate in {}", unit),
+            Self::InvalidRowCount(rows) => write!(f, "expected 9 rows but found {}", rows),
+            Self::InvalidRowLength { row, len } => {
+                write!(f, "expected 9 cells in row {} but found {}", row + 1, len)
+            }
+            Self::InvalidValue { loc, value } => write!(
+                f,
+                "value {} at ({}, {}) must be within range 0..=9",
+                value, loc.0, loc.1
+            ),
+            Self::InvalidField { loc, field } => {
+                write!(
+                    f,
+                    "could not read `{}` at ({}, {}) as a cell",
+                    field, loc.0, loc.1
+                )
+            }
+            Self::InvalidEncoding => write!(f, "input is not a valid encoded sudoku"),
+            Self::Line { line, source } => write!(f, "line {}: {}", line, source),
+        }
     }
 }
 
 impl FromStr for Sudoku {
     type Err = ParseError;
 
-    /// Returns a [`Sudoku`] from a given `src: &str`. Digits are parsed as a number in the sudoku while anything else is a blank space. Newlines are ignored.
+    /// Returns a [`Sudoku`] from a given `src: &str`. Digits `1-9` are parsed as a number in the sudoku while anything else is a blank space. Newlines are ignored.
+    ///
+    /// Both `0` and non-digit characters such as `.` or `x` are treated as blanks, so the common `0`-for-blank and `.`-for-blank formats parse identically.
+    ///
+    /// The first blank character of `src` is remembered and returned by [`Sudoku::blank`], so that `s.to_line_with(s.blank())` writes blanks the way the input did.
     ///
     /// # Examples
     ///
@@ -85,28 +234,55 @@ impl FromStr for Sudoku {
     /// ).unwrap();
     /// ```    
     fn from_str(src: &str) -> Result<Self, Self::Err> {
-        use SudokuNum::*;
-        let xs: [Option<SudokuNum>; 81] = match src
-            .chars()
-            .filter(|&x| x != '\n')
-            .map(|x| {
-                if let Ok(num) = x.to_string().parse::<u8>() {
-                    Some(Original(num))
-                } else {
-                    None
-                }
-            })
-            .collect::<Vec<Option<SudokuNum>>>()
-            .try_into()
-        {
-            Ok(xs) => xs,
-            Err(_) => return Err(ParseError),
-        };
-        Ok(Self { xs })
+        Self::from_str_with(src, &SymbolSet::DIGITS)
     }
 }
 
-#[derive(Debug, PartialEq)]
+impl TryFrom<&str> for Sudoku {
+    type Error = ParseError;
+
+    /// Parses a [`Sudoku`] with [`Sudoku::from_str_validated`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use newdoku::Sudoku;
+    ///
+    /// let lines = format!("{0}\n{0}", ".".repeat(81));
+    /// let sudokus = lines.lines().map(Sudoku::try_from).collect::<Result<Vec<_>, _>>();
+    /// assert_eq!(sudokus.unwrap().len(), 2);
+    /// ```
+    fn try_from(src: &str) -> Result<Self, Self::Error> {
+        Self::from_str_validated(src)
+    }
+}
+
+impl From<&Sudoku> for String {
+    /// Returns the grid as a single line, the same as [`Sudoku::to_line`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use newdoku::Sudoku;
+    /// use std::str::FromStr;
+    ///
+    /// let s = Sudoku::from_str(&"1.......2".repeat(9)).unwrap();
+    /// let line: String = (&s).into();
+    /// assert_eq!(line, s.to_line());
+    /// ```
+    fn from(sudoku: &Sudoku) -> Self {
+        sudoku.to_line()
+    }
+}
+
+impl From<Sudoku> for String {
+    /// Returns the grid as a single line, the same as [`Sudoku::to_line`].
+    fn from(sudoku: Sudoku) -> Self {
+        sudoku.to_line()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum InsertError {
     /// The location provided to insert at is invalid.
     InvalidLoc,
@@ -118,6 +294,24 @@ pub enum InsertError {
     ColDuplicate,
     /// An instance of the number exists already in the 3x3 block.
     BlockDuplicate,
+    /// An instance of the number exists already in an extra region.
+    RegionDuplicate,
+    /// The cell is already filled and cannot be changed.
+    Occupied,
+    /// The number does not increase along a thermometer.
+    ThermoViolation,
+    /// The number cannot be part of digits adding up to the sum of a killer cage.
+    CageViolation,
+    /// An instance of the number exists already in a cell a king's move away.
+    AntiKingViolation,
+    /// An orthogonally adjacent cell holds a number one more or one less.
+    ConsecutiveViolation,
+    /// The number has the wrong parity for the cell.
+    ParityViolation,
+    /// The original clues of two grids being merged differ.
+    OriginalMismatch,
+    /// The number differs from the cell's clone.
+    CloneMismatch,
 }
 
 impl Error for InsertError {}
@@ -134,12 +328,318 @@ impl fmt::Display for InsertError {
                 RowDuplicate => "duplicate instance already in row",
                 ColDuplicate => "duplicate instance already in col",
                 BlockDuplicate => "duplicate instance already in block",
+                RegionDuplicate => "duplicate instance already in region",
+                Occupied => "cell is already filled",
+                ThermoViolation => "number must increase along thermometer",
+                CageViolation => "numbers must add up to the cage sum",
+                AntiKingViolation => "duplicate instance already a king's move away",
+                ConsecutiveViolation => "number must not be consecutive with an adjacent cell",
+                ParityViolation => "number has the wrong parity for cell",
+                OriginalMismatch => "original clues do not match",
+                CloneMismatch => "number must match cloned cell",
             }
         )
     }
 }
 
+#[derive(Debug, PartialEq)]
+pub enum ConstraintError {
+    /// A location in the constraint is invalid.
+    InvalidLoc,
+    /// The region has more cells than there are distinct digits.
+    RegionTooLarge,
+    /// The thermometer has more cells than there are distinct digits.
+    ThermoTooLong,
+    /// The thermometer is not a chain of distinct, adjacent cells.
+    InvalidPath,
+    /// The cloned cells are the same cell or share a unit, so they cannot hold equal numbers.
+    ClonesArePeers,
+    /// The jigsaw regions do not split the grid into nine regions of nine cells.
+    InvalidRegions,
+    /// The killer cage is not a connected group of at most 9 cells, or no distinct digits add up to its sum.
+    InvalidCage,
+    /// The killer layout does not have exactly 81 cells.
+    InvalidLayout,
+    /// The cage marked with the letter in the killer layout has no sum.
+    MissingCageSum(char),
+}
+
+impl Error for ConstraintError {}
+
+impl fmt::Display for ConstraintError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        use ConstraintError::*;
+        match self {
+            InvalidLoc => write!(f, "each loc coordinate must be within range 0..9"),
+            RegionTooLarge => write!(f, "region must contain at most 9 cells"),
+            ThermoTooLong => write!(f, "thermometer must contain at most 9 cells"),
+            InvalidPath => write!(f, "thermometer must be a chain of distinct adjacent cells"),
+            ClonesArePeers => write!(f, "cloned cells must be distinct and not share a unit"),
+            InvalidRegions => write!(f, "jigsaw must have nine regions of nine cells"),
+            InvalidCage => write!(
+                f,
+                "cage must be connected cells whose digits can add up to its sum"
+            ),
+            InvalidLayout => write!(f, "killer layout must contain 81 cells"),
+            MissingCageSum(c) => write!(f, "cage `{}` has no sum", c),
+        }
+    }
+}
+
+/// The sudoku has no solution.
+#[derive(Debug, PartialEq)]
+pub struct NoSolution;
+
+impl Error for NoSolution {}
+
+impl fmt::Display for NoSolution {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(f, "sudoku has no solution")
+    }
+}
+
+/// The sudoku has empty cells where a full grid was required.
+#[derive(Debug, PartialEq)]
+pub struct NotFull;
+
+impl Error for NotFull {}
+
+impl fmt::Display for NotFull {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(f, "sudoku has empty cells")
+    }
+}
+
+/// The sudoku has more solutions than were allowed.
+#[derive(Debug, PartialEq)]
+pub struct TooManySolutions;
+
+impl Error for TooManySolutions {}
+
+impl fmt::Display for TooManySolutions {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(f, "sudoku has too many solutions")
+    }
+}
+
+/// A step passed to [`Sudoku::validate_steps`] could not be applied.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StepError {
+    /// Position of the step in the list, counting from 0.
+    pub index: usize,
+    /// Why the step could not be applied.
+    pub error: InsertError,
+}
+
+impl Error for StepError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+impl fmt::Display for StepError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(f, "step {}: {}", self.index, self.error)
+    }
+}
+
+/// The solution passed to [`SudokuGame::new`] does not solve the puzzle.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SolutionError {
+    /// The solution has empty cells.
+    NotFull,
+    /// The solution breaks sudoku rules or the variant constraints of the puzzle.
+    Invalid,
+    /// The solution differs from a given of the puzzle at `loc: (x, y)`.
+    GivenMismatch { loc: (usize, usize) },
+}
+
+impl Error for SolutionError {}
+
+impl fmt::Display for SolutionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        match self {
+            Self::NotFull => write!(f, "solution has empty cells"),
+            Self::Invalid => write!(f, "solution breaks sudoku rules"),
+            Self::GivenMismatch { loc: (x, y) } => {
+                write!(f, "solution differs from the given at ({}, {})", x, y)
+            }
+        }
+    }
+}
+
 impl Sudoku {
+    /// Returns a [`Sudoku`] holding `xs` with no variant constraints.
+    fn from_cells(xs: [Option<SudokuNum>; 81]) -> Self {
+        Self {
+            xs,
+            variants: Variants::default(),
+            blank: '.',
+        }
+    }
+
+    /// Parses a [`Sudoku`] like [`Sudoku::from_str`], then checks that the givens contain no duplicates in any unit.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use newdoku::{ParseError, Sudoku, Unit};
+    ///
+    /// assert_eq!(
+    ///     Sudoku::from_str_validated(&format!("55{}", ".".repeat(79))),
+    ///     Err(ParseError::Conflict(Unit::Row(0)))
+    /// );
+    /// ```
+    pub fn from_str_validated(src: &str) -> Result<Self, ParseError> {
+        let sudoku = Self::from_str(src)?;
+        match sudoku.find_conflict() {
+            Some(unit) => Err(ParseError::Conflict(unit)),
+            None => Ok(sudoku),
+        }
+    }
+
+    /// Parses each line of `src` as a [`Sudoku`] with [`Sudoku::from_str_validated`], skipping blank lines and comment lines starting with `#`. Each result is paired with the number of its line, counted from 1, and errors are also wrapped in [`ParseError::Line`] with that number so that they can be reported on their own.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use newdoku::{ParseError, Sudoku};
+    ///
+    /// let src = format!("# puzzles\n{}\n\n123\n", ".".repeat(81));
+    /// let results: Vec<_> = Sudoku::parse_lines(&src).collect();
+    /// assert_eq!(results.len(), 2);
+    /// assert_eq!(results[0].0, 2);
+    /// assert!(results[0].1.is_ok());
+    /// assert_eq!(
+    ///     results[1],
+    ///     (4, Err(ParseError::Line { line: 4, source: Box::new(ParseError::InvalidLength(3)) }))
+    /// );
+    /// ```
+    pub fn parse_lines(src: &str) -> impl Iterator<Item = (usize, Result<Self, ParseError>)> + '_ {
+        src.lines()
+            .enumerate()
+            .map(|(i, line)| (i + 1, line.trim()))
+            .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+            .map(|(line, src)| {
+                let result = Self::from_str_validated(src).map_err(|e| ParseError::Line {
+                    line,
+                    source: Box::new(e),
+                });
+                (line, result)
+            })
+    }
+
+    /// Returns a [`Sudoku`] from `rows`, read top to bottom, where `0` is a blank and `1..=9` are givens.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use newdoku::{ParseError, Sudoku};
+    ///
+    /// let mut rows = vec![vec![0; 9]; 9];
+    /// rows[0][0] = 5;
+    /// assert_eq!(Sudoku::from_rows(rows.clone()).unwrap().candidates(1, 0).len(), 8);
+    ///
+    /// rows.pop();
+    /// assert_eq!(Sudoku::from_rows(rows), Err(ParseError::InvalidRowCount(8)));
+    /// ```
+    pub fn from_rows(rows: Vec<Vec<u8>>) -> Result<Self, ParseError> {
+        if rows.len() != 9 {
+            return Err(ParseError::InvalidRowCount(rows.len()));
+        }
+
+        let mut xs = [None; 81];
+        for (y, row) in rows.iter().enumerate() {
+            if row.len() != 9 {
+                return Err(ParseError::InvalidRowLength {
+                    row: y,
+                    len: row.len(),
+                });
+            }
+            for (x, &value) in row.iter().enumerate() {
+                xs[y * 9 + x] = match value {
+                    0 => None,
+                    1..=9 => Some(SudokuNum::Original(value)),
+                    _ => return Err(ParseError::InvalidValue { loc: (x, y), value }),
+                };
+            }
+        }
+
+        Ok(Self::from_cells(xs))
+    }
+
+    /// Returns true if no unit of `self` contains the same number twice and every filled cell satisfies the variant constraints.
+    pub fn is_valid(&self) -> bool {
+        self.find_conflict().is_none()
+            && (0..81).all(|i| match self.xs[i] {
+                Some(num) => self.check_variants(i, num.value()).is_ok(),
+                None => true,
+            })
+    }
+
+    /// Returns every pair of cells `((x, y), (x, y))` which share a row, column, block or extra region and hold the same number. Each pair is listed once, with the cell that comes first in reading order first, and pairs are sorted in reading order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use newdoku::Sudoku;
+    /// use std::str::FromStr;
+    ///
+    /// let s = Sudoku::from_str(&format!("1.1{}", ".".repeat(78))).unwrap();
+    /// assert_eq!(s.conflicts(), vec![((0, 0), (2, 0))]);
+    /// ```
+    pub fn conflicts(&self) -> Vec<((usize, usize), (usize, usize))> {
+        let mut pairs = Vec::new();
+        for unit in self.all_units() {
+            let cells = self.unit_cells(unit);
+            for (i, &a) in cells.iter().enumerate() {
+                for &b in &cells[i + 1..] {
+                    if self.xs[a].is_some() && self.xs[a] == self.xs[b] {
+                        pairs.push((a.min(b), a.max(b)));
+                    }
+                }
+            }
+        }
+        pairs.sort_unstable();
+        pairs.dedup();
+        pairs
+            .into_iter()
+            .map(|(a, b)| ((a % 9, a / 9), (b % 9, b / 9)))
+            .collect()
+    }
+
+    /// Returns true if no row or column of `self` contains the same number twice, ignoring blocks, extra regions and variant constraints. A full grid passes exactly when every row and column is a permutation of 1 to 9.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use newdoku::Sudoku;
+    /// use std::str::FromStr;
+    ///
+    /// let rows: String = (0..9).map(|y| format!("{}{}", &"123456789"[y..], &"123456789"[..y])).collect();
+    /// let s = Sudoku::from_str(&rows).unwrap();
+    /// assert!(s.is_latin_square());
+    /// assert!(!s.is_valid());
+    /// ```
+    pub fn is_latin_square(&self) -> bool {
+        UNITS[..18].iter().all(|cells| !self.has_duplicate(cells))
+    }
+
+    /// Returns the first unit containing a duplicate, if any.
+    fn find_conflict(&self) -> Option<Unit> {
+        self.all_units()
+            .find(|&unit| self.has_duplicate(self.unit_cells(unit)))
+    }
+
+    /// Returns true if two of `cells` hold the same number.
+    fn has_duplicate(&self, cells: &[usize]) -> bool {
+        let mut seen = [false; 10];
+        cells.iter().any(|&i| match self.xs[i] {
+            Some(num) => core::mem::replace(&mut seen[num.value() as usize], true),
+            None => false,
+        })
+    }
+
     /// Returns a [`Sudoku`] that is the same as `self` but with `num` inserted at `loc: (x, y)` (0-indexed) if it can be inserted there by sudoku rules.
     pub fn try_insert(&self, loc: (usize, usize), num: u8) -> Result<Self, InsertError> {
         use InsertError::*;
@@ -148,37 +648,578 @@ impl Sudoku {
         if loc.0 > 8 || loc.1 > 8 {
             return Err(InvalidLoc);
         }
-        if num > 9 {
+        if num == 0 || num > 9 {
+            return Err(InvalidNumber);
+        }
+
+        let idx = loc.1 * 9 + loc.0;
+        self.check(idx, num)?;
+
+        let mut sudoku = self.clone();
+        sudoku.xs[idx] = Some(Edited(num));
+        Ok(sudoku)
+    }
+
+    /// Places `num` at `(x, y)` (0-indexed) as an original clue if it can be inserted there by sudoku rules, as with [`Sudoku::try_insert`]. Unlike edits, clues are shown in bold and cannot be changed in a [`SudokuGame`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use newdoku::{InsertError, Sudoku};
+    /// use std::str::FromStr;
+    ///
+    /// let mut s = Sudoku::from_str(&".".repeat(81)).unwrap();
+    /// s.with_given(0, 0, 5).unwrap();
+    /// assert_eq!(s.with_given(8, 0, 5), Err(InsertError::RowDuplicate));
+    /// assert_eq!(s.with_given(0, 0, 6), Err(InsertError::Occupied));
+    /// ```
+    pub fn with_given(&mut self, x: usize, y: usize, num: u8) -> Result<(), InsertError> {
+        use InsertError::*;
+
+        if x > 8 || y > 8 {
+            return Err(InvalidLoc);
+        }
+        if num == 0 || num > 9 {
             return Err(InvalidNumber);
         }
 
-        let mut xs = self.xs.clone();
+        let idx = y * 9 + x;
+        if self.xs[idx].is_some() {
+            return Err(Occupied);
+        }
+        self.check(idx, num)?;
+        self.xs[idx] = Some(SudokuNum::Original(num));
+        Ok(())
+    }
+
+    /// Returns a [`Sudoku`] that is the same as `self` but with each edited cell of `other` inserted by [`Sudoku::try_insert`].
+    ///
+    /// Edits already present in `self` are skipped. Fails if the original clues of the two grids differ, or if any edit conflicts with `self`.
+    pub fn merge_edits(&self, other: &Self) -> Result<Self, InsertError> {
+        use SudokuNum::*;
+
+        let originals_match = self.xs.iter().zip(other.xs.iter()).all(|pair| match pair {
+            (Some(Original(a)), Some(Original(b))) => a == b,
+            (Some(Original(_)), _) | (_, Some(Original(_))) => false,
+            _ => true,
+        });
+        if !originals_match {
+            return Err(InsertError::OriginalMismatch);
+        }
+
+        let mut sudoku = self.clone();
+        for (idx, x) in other.xs.iter().enumerate() {
+            if let Some(Edited(num)) = *x {
+                match sudoku.xs[idx] {
+                    Some(existing) if existing.value() == num => {}
+                    Some(_) => return Err(InsertError::Occupied),
+                    None => sudoku = sudoku.try_insert((idx % 9, idx / 9), num)?,
+                }
+            }
+        }
+        Ok(sudoku)
+    }
+
+    /// Checks that each step `(x, y, num)` of `steps` can be applied in order with [`Sudoku::try_insert`], starting from `self`, and that each fills a cell which was empty. Returns the first step which cannot be applied, with [`InsertError::Occupied`] if its cell was already filled and otherwise the error of [`Sudoku::try_insert`].
+    ///
+    /// Only the rules are checked, so a legal step may still be a mistake which leaves the grid without a solution.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use newdoku::{InsertError, StepError, Sudoku};
+    /// use std::str::FromStr;
+    ///
+    /// let s = Sudoku::from_str(&format!("1{}", ".".repeat(80))).unwrap();
+    /// assert_eq!(s.validate_steps(&[(1, 0, 2), (2, 0, 3)]), Ok(()));
+    /// assert_eq!(
+    ///     s.validate_steps(&[(1, 0, 2), (1, 0, 3)]),
+    ///     Err(StepError { index: 1, error: InsertError::Occupied })
+    /// );
+    /// assert_eq!(
+    ///     s.validate_steps(&[(0, 1, 1)]),
+    ///     Err(StepError { index: 0, error: InsertError::ColDuplicate })
+    /// );
+    /// ```
+    pub fn validate_steps(&self, steps: &[(usize, usize, u8)]) -> Result<(), StepError> {
+        let mut sudoku = self.clone();
+        for (index, &(x, y, num)) in steps.iter().enumerate() {
+            let result = match x < 9 && y < 9 && sudoku.xs[y * 9 + x].is_some() {
+                true => Err(InsertError::Occupied),
+                false => sudoku.try_insert((x, y), num),
+            };
+            sudoku = result.map_err(|error| StepError { index, error })?;
+        }
+        Ok(())
+    }
+
+    /// Checks whether `num` may be placed at index `idx` without breaking any constraint.
+    fn check(&self, idx: usize, num: u8) -> Result<(), InsertError> {
+        use InsertError::*;
+
+        for unit in self.units_of(idx) {
+            if self
+                .unit_cells(unit)
+                .iter()
+                .any(|&i| self.xs[i].map(SudokuNum::value) == Some(num))
+            {
+                return Err(match unit {
+                    Unit::Row(_) => RowDuplicate,
+                    Unit::Col(_) => ColDuplicate,
+                    Unit::Block(_) => BlockDuplicate,
+                    Unit::Region(_) => RegionDuplicate,
+                });
+            }
+        }
+
+        self.check_variants(idx, num)
+    }
+
+    /// Returns the numbers which can be inserted at `(x, y)` by sudoku rules, in ascending order. Filled cells have no candidates.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `x` or `y` is not within range 0..9.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use newdoku::Sudoku;
+    /// use std::str::FromStr;
+    ///
+    /// let s = Sudoku::from_str(
+    ///     "xxxxxxx9xx9x7xx21xxx4x9xxxxx1xxx8xxx7xx42xxx5xx8xxxx748x1xxxx4xxxxxxxxxxxx9613xxx",
+    /// ).unwrap();
+    /// assert_eq!(s.candidates(0, 0), vec![1, 2, 3, 5, 6]);
+    /// ```
+    pub fn candidates(&self, x: usize, y: usize) -> Vec<u8> {
+        let mask = self.candidates_mask(x, y);
+        (1..10).filter(|num| mask & 1 << (num - 1) != 0).collect()
+    }
+
+    /// Returns the candidates of `(x, y)` like [`Sudoku::candidates`], but as a bitset without allocating. Bit `n - 1` is set if `n` is a candidate, so bit 0 stands for 1 and bit 8 for 9, and the upper bits are always clear.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `x` or `y` is not within range 0..9.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use newdoku::Sudoku;
+    /// use std::str::FromStr;
+    ///
+    /// let s = Sudoku::from_str(
+    ///     "xxxxxxx9xx9x7xx21xxx4x9xxxxx1xxx8xxx7xx42xxx5xx8xxxx748x1xxxx4xxxxxxxxxxxx9613xxx",
+    /// ).unwrap();
+    /// assert_eq!(s.candidates_mask(0, 0), 0b000110111);
+    /// ```
+    pub fn candidates_mask(&self, x: usize, y: usize) -> u16 {
+        assert!(x < 9 && y < 9, "each coordinate must be within range 0..9");
+        self.candidate_mask(y * 9 + x)
+    }
+
+    /// Returns the candidates of the cell at index `idx` with bit `n - 1` set for each candidate `n`.
+    fn candidate_mask(&self, idx: usize) -> u16 {
+        if self.xs[idx].is_some() {
+            return 0;
+        }
+        let mut mask = 0x1ff;
+        for unit in self.units_of(idx) {
+            for &i in self.unit_cells(unit) {
+                if let Some(num) = self.xs[i] {
+                    mask &= !(1 << (num.value() - 1));
+                }
+            }
+        }
+        for num in 1..10 {
+            if mask & 1 << (num - 1) != 0 && self.check_variants(idx, num).is_err() {
+                mask &= !(1 << (num - 1));
+            }
+        }
+        for &(i, num) in &self.variants.excluded {
+            if i == idx {
+                mask &= !(1 << (num - 1));
+            }
+        }
+        mask
+    }
+
+    /// Returns the sum of [`Sudoku::candidates`] over every empty cell of `self`.
+    pub fn total_candidates(&self) -> usize {
+        (0..81).map(|i| self.candidates(i % 9, i / 9).len()).sum()
+    }
+
+    /// Returns the mean number of [`Sudoku::candidates`] over the empty cells of `self`, a rough measure of how widely a search must branch. A full grid has a branching factor of `0.0`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use newdoku::Sudoku;
+    /// use std::str::FromStr;
+    ///
+    /// assert_eq!(Sudoku::from_str(&".".repeat(81)).unwrap().branching_factor(), 9.0);
+    /// ```
+    pub fn branching_factor(&self) -> f64 {
+        let empty = self.xs.iter().filter(|x| x.is_none()).count();
+        if empty == 0 {
+            0.0
+        } else {
+            self.total_candidates() as f64 / empty as f64
+        }
+    }
+
+    /// Returns the units containing the cell at index `idx`: its row, column, block and any extra regions.
+    fn units_of(&self, idx: usize) -> impl Iterator<Item = Unit> + '_ {
+        [
+            Unit::Row(idx / 9),
+            Unit::Col(idx % 9),
+            Unit::Block(self.block_of(idx)),
+        ]
+        .into_iter()
+        .chain(
+            self.variants
+                .regions
+                .iter()
+                .enumerate()
+                .filter(move |(_, region)| region.contains(&idx))
+                .map(|(i, _)| Unit::Region(i)),
+        )
+    }
+
+    /// Returns every row, column and block, followed by any extra regions.
+    fn all_units(&self) -> impl Iterator<Item = Unit> {
+        (0..9)
+            .map(Unit::Row)
+            .chain((0..9).map(Unit::Col))
+            .chain((0..9).map(Unit::Block))
+            .chain((0..self.variants.regions.len()).map(Unit::Region))
+    }
+
+    /// Returns the indices of the cells in `unit`.
+    fn unit_cells(&self, unit: Unit) -> &[usize] {
+        match unit {
+            Unit::Row(i) => &UNITS[i],
+            Unit::Col(i) => &UNITS[9 + i],
+            Unit::Block(i) => match &self.variants.jigsaw {
+                Some(jigsaw) => &jigsaw.cells[i],
+                None => &UNITS[18 + i],
+            },
+            Unit::Region(i) => &self.variants.regions[i],
+        }
+    }
+
+    /// Returns the index of the block containing the cell at index `idx`, which is one of the regions set with [`Sudoku::with_regions`] if any.
+    fn block_of(&self, idx: usize) -> usize {
+        match &self.variants.jigsaw {
+            Some(jigsaw) => jigsaw.region_of[idx] as usize,
+            None => idx / 27 * 3 + idx % 9 / 3,
+        }
+    }
+
+    /// Returns the cell indices of each row, column and block like [`UNITS`], with the blocks replaced by any regions set with [`Sudoku::with_regions`].
+    pub(crate) fn houses(&self) -> [[usize; 9]; 27] {
+        let mut houses = UNITS;
+        if let Some(jigsaw) = &self.variants.jigsaw {
+            houses[18..].copy_from_slice(&jigsaw.cells);
+        }
+        houses
+    }
+
+    /// Returns the locations `(x, y)` of every other cell sharing a row, column, block or extra region with `(x, y)`, without duplicates.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `x` or `y` is not within range 0..9.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use newdoku::Sudoku;
+    /// use std::str::FromStr;
+    ///
+    /// let s = Sudoku::from_str(&".".repeat(81)).unwrap();
+    /// assert_eq!(s.peers(4, 4).len(), 20);
+    /// ```
+    pub fn peers(&self, x: usize, y: usize) -> Vec<(usize, usize)> {
+        assert!(x < 9 && y < 9, "each coordinate must be within range 0..9");
+        let idx = y * 9 + x;
+        let mut peers: Vec<(usize, usize)> = Vec::new();
+        for unit in self.units_of(idx) {
+            for &i in self.unit_cells(unit) {
+                if i != idx && !peers.contains(&(i % 9, i / 9)) {
+                    peers.push((i % 9, i / 9));
+                }
+            }
+        }
+        peers
+    }
+
+    /// Returns the locations `(x, y)` of the peers of `(x, y)`, as returned by [`Sudoku::peers`], which already hold `num`, in reading order. These are the cells which stop `num` being inserted at `(x, y)` when [`Sudoku::try_insert`] reports a duplicate.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `x` or `y` is not within range 0..9, or `num` is not within range 1..=9.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use newdoku::{InsertError, Sudoku};
+    /// use std::str::FromStr;
+    ///
+    /// let s = Sudoku::from_str(&format!("7.......7{}.7{}", ".".repeat(9), ".".repeat(61))).unwrap();
+    /// assert_eq!(s.try_insert((1, 0), 7), Err(InsertError::RowDuplicate));
+    /// assert_eq!(s.neighbors_sharing_digit(1, 0, 7), vec![(0, 0), (8, 0), (1, 2)]);
+    /// assert!(s.neighbors_sharing_digit(1, 0, 3).is_empty());
+    /// ```
+    pub fn neighbors_sharing_digit(&self, x: usize, y: usize, num: u8) -> Vec<(usize, usize)> {
+        assert!(x < 9 && y < 9, "each coordinate must be within range 0..9");
+        assert!((1..=9).contains(&num), "number must be within range 1..=9");
+        let mut cells: Vec<(usize, usize)> = self
+            .peers(x, y)
+            .into_iter()
+            .filter(|&(px, py)| self.xs[py * 9 + px].map(SudokuNum::value) == Some(num))
+            .collect();
+        cells.sort_unstable_by_key(|&(x, y)| (y, x));
+        cells
+    }
+
+    /// Returns the number of times the solver has to guess while solving `self`, or [`None`] if there is no solution.
+    ///
+    /// Cells with only one candidate are filled without guessing, so a puzzle which is solvable by naked singles alone needs zero guesses. Every branch on a cell with more than one candidate counts as a guess, including those that lead to dead ends.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use newdoku::Sudoku;
+    /// use std::str::FromStr;
+    ///
+    /// let s = Sudoku::from_str(
+    ///     "157832496396745218284196753415378962763429185928561374831257649672984531549613827",
+    /// ).unwrap();
+    /// assert_eq!(s.guess_count(), Some(0));
+    /// ```
+    pub fn guess_count(&self) -> Option<usize> {
+        let mut stats = solver::Stats::default();
+        solver::solve(self, &mut stats).map(|_| stats.guesses)
+    }
+
+    /// Returns `self` as a single line of 81 characters, read left to right and then top to bottom, with `.` for blanks.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use newdoku::Sudoku;
+    /// use std::str::FromStr;
+    ///
+    /// let line = "1.......2".repeat(9);
+    /// assert_eq!(Sudoku::from_str(&line).unwrap().to_line(), line);
+    /// ```
+    pub fn to_line(&self) -> String {
+        self.to_line_with('.')
+    }
+
+    /// Returns `self` as a single line like [`Sudoku::to_line`], but with `blank` for blanks.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use newdoku::Sudoku;
+    /// use std::str::FromStr;
+    ///
+    /// let line = "1000000020".repeat(8) + "0";
+    /// let s = Sudoku::from_str(&line).unwrap();
+    /// assert_eq!(s.to_line_with(s.blank()), line);
+    /// assert_eq!(s.to_line_with('_'), line.replace('0', "_"));
+    /// ```
+    pub fn to_line_with(&self, blank: char) -> String {
+        self.xs
+            .iter()
+            .map(|x| match x {
+                Some(num) => (b'0' + num.value()) as char,
+                None => blank,
+            })
+            .collect()
+    }
+
+    /// Returns the character used for blanks in the input `self` was parsed from with [`Sudoku::from_str`] or a parser built on it, such as [`Sudoku::from_str_validated`]. If the input mixed several, the first is returned. Grids built any other way, or parsed from a grid without blanks, give `.`.
+    pub fn blank(&self) -> char {
+        self.blank
+    }
+
+    /// Returns how many times each number appears in `self`, with the count of 1 first. Givens and edits are both counted, so a full grid gives 9 for every number.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use newdoku::Sudoku;
+    /// use std::str::FromStr;
+    ///
+    /// let s = Sudoku::from_str(&format!("1..1...9.{}", ".".repeat(72))).unwrap();
+    /// assert_eq!(s.digit_counts(), [2, 0, 0, 0, 0, 0, 0, 0, 1]);
+    /// ```
+    pub fn digit_counts(&self) -> [u8; 9] {
+        let mut counts = [0; 9];
+        for num in self.xs.iter().flatten() {
+            counts[num.value() as usize - 1] += 1;
+        }
+        counts
+    }
+
+    /// Returns the fraction of cells which are filled, from `0.0` for an empty grid to `1.0` for a full one.
+    pub fn progress(&self) -> f32 {
+        self.xs.iter().filter(|x| x.is_some()).count() as f32 / 81.0
+    }
+
+    /// Returns a copy of `self` with every edited cell cleared, leaving only the givens.
+    pub(crate) fn givens(&self) -> Self {
+        let mut givens = self.clone();
+        for x in givens.xs.iter_mut() {
+            if matches!(x, Some(SudokuNum::Edited(_))) {
+                *x = None;
+            }
+        }
+        givens
+    }
+
+    /// Returns the fraction of originally empty cells which have since been filled, from `0.0` to `1.0`.
+    ///
+    /// A grid with no originally empty cells counts as complete.
+    pub fn edit_progress(&self) -> f32 {
+        let empty = self
+            .xs
+            .iter()
+            .filter(|x| !matches!(x, Some(SudokuNum::Original(_))))
+            .count();
+        let edited = self
+            .xs
+            .iter()
+            .filter(|x| matches!(x, Some(SudokuNum::Edited(_))))
+            .count();
+        if empty == 0 {
+            1.0
+        } else {
+            edited as f32 / empty as f32
+        }
+    }
+
+    /// Returns the indices of the rows, columns and blocks of `self` which are filled with nine distinct digits, in that order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use newdoku::Sudoku;
+    /// use std::str::FromStr;
+    ///
+    /// let s = Sudoku::from_str(&format!("123456789{}", ".".repeat(72))).unwrap();
+    /// assert_eq!(s.completed_units(), (vec![0], vec![], vec![]));
+    /// ```
+    pub fn completed_units(&self) -> (Vec<usize>, Vec<usize>, Vec<usize>) {
+        let completed = |unit: fn(usize) -> Unit| -> Vec<usize> {
+            (0..9)
+                .filter(|&i| {
+                    let mut seen = 0u16;
+                    for &idx in self.unit_cells(unit(i)) {
+                        match self.xs[idx] {
+                            Some(num) => seen |= 1 << (num.value() - 1),
+                            None => return false,
+                        }
+                    }
+                    seen == 0x1ff
+                })
+                .collect()
+        };
+        (
+            completed(Unit::Row),
+            completed(Unit::Col),
+            completed(Unit::Block),
+        )
+    }
+
+    /// Returns the values of every 3x3 block of `self`, with blocks ordered left to right and then top to bottom, and the cells of each block in the same order. For a jigsaw sudoku set up with [`Sudoku::with_regions`], the regions are returned in order of their index instead, each with its cells in reading order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use newdoku::Sudoku;
+    /// use std::str::FromStr;
+    ///
+    /// let s = Sudoku::from_str(&format!("...4{}", ".".repeat(77))).unwrap();
+    /// assert_eq!(s.box_values()[1][0], Some(4));
+    /// ```
+    pub fn box_values(&self) -> [[Option<u8>; 9]; 9] {
+        core::array::from_fn(|b| {
+            let cells = self.unit_cells(Unit::Block(b));
+            core::array::from_fn(|i| self.xs[cells[i]].map(|num| num.value()))
+        })
+    }
 
-        for x in 0..9 {
-            if (xs[loc.1 * 9 + x] == Some(Original(num))) | (xs[loc.1 * 9 + x] == Some(Edited(num)))
-            {
-                return Err(RowDuplicate);
-            }
-            if (xs[x * 9 + loc.0] == Some(Original(num))) | (xs[x * 9 + loc.0] == Some(Edited(num)))
-            {
-                return Err(ColDuplicate);
-            }
-        }
+    /// Returns where the givens of `self` are, indexed by row and then column, with `true` for every [`SudokuNum::Original`] cell. Edited and empty cells are `false`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use newdoku::Sudoku;
+    /// use std::str::FromStr;
+    ///
+    /// let s = Sudoku::from_str(&format!("...4{}", ".".repeat(77)))
+    ///     .unwrap()
+    ///     .try_insert((0, 1), 1)
+    ///     .unwrap();
+    /// let map = s.clue_density_map();
+    /// assert!(map[0][3]);
+    /// assert!(!map[1][0]);
+    /// assert_eq!(map.iter().flatten().filter(|&&given| given).count(), 1);
+    /// ```
+    pub fn clue_density_map(&self) -> [[bool; 9]; 9] {
+        core::array::from_fn(|y| {
+            core::array::from_fn(|x| matches!(self.xs[y * 9 + x], Some(SudokuNum::Original(_))))
+        })
+    }
 
-        let rel_center = |origin| origin + 1 - origin % 3;
-        let center = (rel_center(loc.0), rel_center(loc.1));
+    /// Returns the cells of `self` as characters indexed by row and then column, with no borders or colours. Numbers are their digits and empty cells are [`Sudoku::blank`], so a grid keeps the blank of its input.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use newdoku::Sudoku;
+    /// use std::str::FromStr;
+    ///
+    /// let s = Sudoku::from_str(&format!("_3{}", "_".repeat(79))).unwrap();
+    /// let grid = s.char_grid();
+    /// assert_eq!(grid[0][..3], ['_', '3', '_']);
+    /// assert_eq!(grid.iter().flatten().collect::<String>(), s.to_line_with('_'));
+    /// ```
+    pub fn char_grid(&self) -> [[char; 9]; 9] {
+        core::array::from_fn(|y| {
+            core::array::from_fn(|x| match self.xs[y * 9 + x] {
+                Some(num) => (b'0' + num.value()) as char,
+                None => self.blank,
+            })
+        })
+    }
 
-        for i in -1..2 {
-            for j in -1..2 {
-                let x = xs[((center.1 as isize + j) * 9 + center.0 as isize + i) as usize];
-                if (x == Some(Original(num))) | (x == Some(Edited(num))) {
-                    return Err(BlockDuplicate);
-                }
-            }
+    /// Returns the numbers of a full grid indexed by row and then column, without distinguishing givens from edits, or [`NotFull`] if any cell is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use newdoku::{NotFull, Sudoku};
+    /// use std::str::FromStr;
+    ///
+    /// let s = Sudoku::from_str(
+    ///     "xxxxxxx9xx9x7xx21xxx4x9xxxxx1xxx8xxx7xx42xxx5xx8xxxx748x1xxxx4xxxxxxxxxxxx9613xxx",
+    /// ).unwrap();
+    /// assert_eq!(s.solution_grid_only(), Err(NotFull));
+    /// let grid = s.solution(0, true).unwrap().solution_grid_only().unwrap();
+    /// assert_eq!(grid[0], [1, 5, 7, 8, 3, 2, 4, 9, 6]);
+    /// ```
+    pub fn solution_grid_only(&self) -> Result<[[u8; 9]; 9], NotFull> {
+        let mut grid = [[0; 9]; 9];
+        for (idx, x) in self.xs.iter().enumerate() {
+            grid[idx / 9][idx % 9] = x.ok_or(NotFull)?.value();
         }
-
-        xs[loc.1 * 9 + loc.0] = Some(Edited(num));
-        Ok(Self { xs })
+        Ok(grid)
     }
 
     /// Returns true if `self` has no empty spaces.
@@ -190,8 +1231,11 @@ impl Sudoku {
         }
         true
     }
+}
 
-    /// Returns the solved [`Sudoku`] if it exists. If `quiet` set to false, then prints each iteration while solving.
+#[cfg(feature = "std")]
+impl Sudoku {
+    /// Returns the solved [`Sudoku`] if it exists. If `quiet` set to false, then prints each iteration while solving, waiting `step` milliseconds after each. Use [`Sudoku::solution_with`] for finer steps.
     ///
     /// # Examples
     ///
@@ -210,26 +1254,78 @@ impl Sudoku {
     /// );
     /// ```
     pub fn solution(&self, step: u64, quiet: bool) -> Option<Self> {
-        print!("{}", cursor::Hide);
+        self.solution_with(Duration::from_millis(step), quiet, &Theme::default())
+    }
 
-        if self.is_full() {
+    /// Returns the solved [`Sudoku`] like [`Sudoku::solution`], drawing each iteration with `theme` and waiting `step` after each. A zero `step` does not wait at all, while a step below a millisecond gives a fast but visible animation.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use newdoku::{Sudoku, Theme};
+    /// use std::{str::FromStr, time::Duration};
+    ///
+    /// let s = Sudoku::from_str(
+    ///     "xxxxxxx9xx9x7xx21xxx4x9xxxxx1xxx8xxx7xx42xxx5xx8xxxx748x1xxxx4xxxxxxxxxxxx9613xxx",
+    /// ).unwrap();
+    /// let theme = Theme::new().blank('_');
+    /// let step = Duration::from_micros(200);
+    /// assert_eq!(s.solution_with(step, true, &theme), s.solution(0, true));
+    /// ```
+    pub fn solution_with(&self, step: Duration, quiet: bool, theme: &Theme) -> Option<Self> {
+        self.solution_ordered(step, quiet, theme, &mut |_| {})
+    }
+
+    /// Returns the solved [`Sudoku`] like [`Sudoku::solution_with`], letting `order` rearrange the numbers tried in each cell.
+    fn solution_ordered(
+        &self,
+        step: Duration,
+        quiet: bool,
+        theme: &Theme,
+        order: &mut dyn FnMut(&mut [u8]),
+    ) -> Option<Self> {
+        if !quiet {
+            print!("{}", cursor::Hide);
+        }
+        let solution = self.animate(
+            step,
+            quiet,
+            &mut display::Animation::new(terminal::size().ok(), theme.clone()),
+            order,
+        );
+        if !quiet {
             print!("{}", cursor::Show);
-            return Some(*self);
+        }
+        solution
+    }
+
+    /// Solves `self` like [`Sudoku::solution`], drawing each step as the next frame of `animation` and trying the numbers of each cell in the order left by `order`.
+    fn animate(
+        &self,
+        step: Duration,
+        quiet: bool,
+        animation: &mut display::Animation,
+        order: &mut dyn FnMut(&mut [u8]),
+    ) -> Option<Self> {
+        if self.is_full() {
+            return Some(self.clone());
         }
 
         for i in 0..9 {
             for j in 0..9 {
                 if self.xs[i * 9 + j].is_none() {
-                    for x in 1..10 {
+                    let mut nums = [1, 2, 3, 4, 5, 6, 7, 8, 9];
+                    order(&mut nums);
+                    for x in nums {
                         if let Ok(sudoku) = self.try_insert((j, i), x) {
                             if !quiet {
-                                println!("{}\n\n{}", sudoku, cursor::MoveUp(15));
+                                animation.draw(&sudoku);
                             }
-                            if step > 0 {
-                                thread::sleep(Duration::from_millis(step));
+                            if !step.is_zero() {
+                                thread::sleep(step);
                             }
 
-                            if let Some(sudoku) = sudoku.solution(step, quiet) {
+                            if let Some(sudoku) = sudoku.animate(step, quiet, animation, order) {
                                 return Some(sudoku);
                             }
                         }
@@ -239,7 +1335,6 @@ impl Sudoku {
             }
         }
 
-        print!("{}", cursor::Show);
         None
     }
 }
@@ -256,51 +1351,6 @@ impl PartialEq for Sudoku {
     }
 }
 
-impl fmt::Display for Sudoku {
-    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
-        use SudokuNum::*;
-        let mut xs = self.xs.iter();
-        for row in 0..13 {
-            match row {
-                0 | 4 | 8 => {
-                    writeln!(f, "+-------+-------+-------+")?;
-                }
-                12 => {
-                    write!(f, "+-------+-------+-------+")?;
-                }
-                _ => {
-                    write!(f, "| ")?;
-                    for x in 0..11 {
-                        match x {
-                            3 | 7 => {
-                                write!(f, "| ")?;
-                            }
-                            _ => {
-                                if let Some(num) = xs.next().unwrap() {
-                                    match num {
-                                        Original(num) => write!(
-                                            f,
-                                            "{}{}{} ",
-                                            Attribute::Bold,
-                                            num,
-                                            Attribute::Reset
-                                        )?,
-                                        Edited(num) => write!(f, "{} ", num)?,
-                                    }
-                                } else {
-                                    write!(f, ". ")?;
-                                }
-                            }
-                        }
-                    }
-                    writeln!(f, "|")?;
-                }
-            }
-        }
-        Ok(())
-    }
-}
-
 #[cfg(test)]
 mod test {
     use super::{InsertError::*, *};
@@ -342,6 +1392,80 @@ mod test {
         s.solution(0, true).unwrap();
     }
 
+    #[test]
+    fn from_str_invalid_length() {
+        assert_eq!(
+            Sudoku::from_str(&TEST_SUDOKU[1..]),
+            Err(ParseError::InvalidLength(80))
+        );
+    }
+
+    #[test]
+    fn from_str_validated() {
+        assert!(Sudoku::from_str_validated(TEST_SUDOKU).is_ok());
+        assert_eq!(
+            Sudoku::from_str_validated(
+                "xxxxxxx9xx9x7xx21xxx4x9xxxxx1xxx8xxx7xx42xxx5xx8xxxx748x1xxxx4xxxxxxxxxxxx9613xx5",
+            ),
+            Err(ParseError::Conflict(Unit::Col(8)))
+        );
+    }
+
+    #[test]
+    fn parse_lines() {
+        let src = format!(
+            "# header\n\n  {}  \n{}\n# {}\n11{}\n",
+            TEST_SUDOKU,
+            SOLVED_SUDOKU,
+            TEST_SUDOKU,
+            ".".repeat(79)
+        );
+        let (lines, results): (Vec<_>, Vec<_>) = Sudoku::parse_lines(&src).unzip();
+        assert_eq!(lines, vec![3, 4, 6]);
+        assert_eq!(results[0], Ok(Sudoku::from_str(TEST_SUDOKU).unwrap()));
+        assert_eq!(results[1], Ok(Sudoku::from_str(SOLVED_SUDOKU).unwrap()));
+        let err = results[2].as_ref().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "line 6: givens contain a duplicate in row 1"
+        );
+        assert!(err.source().is_some());
+    }
+
+    #[test]
+    fn from_rows() {
+        let rows: Vec<Vec<u8>> = TEST_SUDOKU
+            .as_bytes()
+            .chunks(9)
+            .map(|row| {
+                row.iter()
+                    .map(|&c| if c == b'x' { 0 } else { c - b'0' })
+                    .collect()
+            })
+            .collect();
+        assert_eq!(
+            Sudoku::from_rows(rows.clone()).unwrap(),
+            Sudoku::from_str(TEST_SUDOKU).unwrap()
+        );
+
+        let mut short = rows.clone();
+        short[3].pop();
+        assert_eq!(
+            Sudoku::from_rows(short),
+            Err(ParseError::InvalidRowLength { row: 3, len: 8 })
+        );
+
+        let mut large = rows;
+        large[1][2] = 10;
+        assert_eq!(
+            Sudoku::from_rows(large),
+            Err(ParseError::InvalidValue {
+                loc: (2, 1),
+                value: 10
+            })
+        );
+    }
+
     #[test]
     fn try_insert() {
         let s1 = Sudoku::from_str(TEST_SUDOKU)
@@ -355,6 +1479,52 @@ mod test {
         assert_eq!(s1, s2);
     }
 
+    #[test]
+    fn with_given() {
+        let mut s = Sudoku::from_str(TEST_SUDOKU).unwrap();
+        s.with_given(0, 0, 1).unwrap();
+        assert!(matches!(s.xs[0], Some(SudokuNum::Original(_))));
+        assert_eq!(s.with_given(1, 0, 9), Err(RowDuplicate));
+        assert_eq!(s.with_given(9, 0, 1), Err(InvalidLoc));
+        assert_eq!(s.with_given(1, 0, 0), Err(InvalidNumber));
+        assert_eq!(s.with_given(7, 0, 9), Err(Occupied));
+    }
+
+    #[test]
+    fn merge_edits() {
+        let s = Sudoku::from_str(TEST_SUDOKU).unwrap();
+        let a = s.try_insert((0, 0), 1).unwrap();
+        let b = s
+            .try_insert((0, 0), 1)
+            .unwrap()
+            .try_insert((1, 0), 2)
+            .unwrap();
+        let merged = a.merge_edits(&b).unwrap();
+        assert_eq!(merged, b);
+        assert_eq!(merged.xs[1], Some(SudokuNum::Edited(2)));
+
+        let c = s.try_insert((0, 0), 3).unwrap();
+        assert_eq!(a.merge_edits(&c), Err(Occupied));
+        let d = s.try_insert((3, 0), 1).unwrap();
+        assert_eq!(a.merge_edits(&d), Err(RowDuplicate));
+        let other = Sudoku::from_str(&"x".repeat(81)).unwrap();
+        assert_eq!(a.merge_edits(&other), Err(OriginalMismatch));
+    }
+
+    #[test]
+    fn try_insert_zero() {
+        let s1 = Sudoku::from_str(TEST_SUDOKU).unwrap();
+        assert_eq!(s1.try_insert((0, 0), 0), Err(InvalidNumber));
+    }
+
+    #[test]
+    fn parse_zero_as_blank() {
+        assert_eq!(
+            Sudoku::from_str(&TEST_SUDOKU.replace('x', "0")).unwrap(),
+            Sudoku::from_str(&TEST_SUDOKU.replace('x', ".")).unwrap()
+        );
+    }
+
     #[test]
     fn try_insert_row() {
         let s1 = Sudoku::from_str(TEST_SUDOKU).unwrap();
@@ -374,12 +1544,313 @@ mod test {
     }
 
     #[test]
+    fn validate_steps() {
+        let s = Sudoku::from_str(TEST_SUDOKU).unwrap();
+        let solved = Sudoku::from_str(SOLVED_SUDOKU).unwrap();
+        let mut steps: Vec<(usize, usize, u8)> = (0..81)
+            .filter(|&i| s.xs[i].is_none())
+            .map(|i| (i % 9, i / 9, solved.xs[i].unwrap().value()))
+            .collect();
+        assert_eq!(s.validate_steps(&steps), Ok(()));
+        assert_eq!(s.validate_steps(&[]), Ok(()));
+
+        steps.insert(3, (7, 0, 9));
+        assert_eq!(
+            s.validate_steps(&steps),
+            Err(StepError {
+                index: 3,
+                error: Occupied
+            })
+        );
+        assert_eq!(
+            s.validate_steps(&[(0, 0, 1), (9, 0, 1)]),
+            Err(StepError {
+                index: 1,
+                error: InvalidLoc
+            })
+        );
+    }
+
+    #[test]
+    fn add_region_invalid() {
+        let mut s = Sudoku::from_str(TEST_SUDOKU).unwrap();
+        assert_eq!(
+            s.add_region(vec![(0, 0), (9, 0)]),
+            Err(ConstraintError::InvalidLoc)
+        );
+        assert_eq!(
+            s.add_region((0..10).map(|i| (i % 9, i / 9)).collect()),
+            Err(ConstraintError::RegionTooLarge)
+        );
+    }
+
+    #[test]
+    fn try_insert_region() {
+        let s = Sudoku::from_str(&".".repeat(81))
+            .unwrap()
+            .with_diagonals()
+            .try_insert((0, 0), 5)
+            .unwrap();
+        assert_eq!(s.try_insert((4, 4), 5), Err(RegionDuplicate));
+        assert!(s.try_insert((4, 3), 5).is_ok());
+    }
+
+    #[test]
+    fn peers() {
+        let s = Sudoku::from_str(TEST_SUDOKU).unwrap();
+        for y in 0..9 {
+            for x in 0..9 {
+                let peers = s.peers(x, y);
+                assert_eq!(peers.len(), 20);
+                assert!(!peers.contains(&(x, y)));
+            }
+        }
+        assert_eq!(s.with_diagonals().peers(0, 0).len(), 26);
+    }
+
+    #[test]
+    #[should_panic(expected = "each coordinate must be within range 0..9")]
+    fn peers_out_of_range() {
+        Sudoku::from_str(TEST_SUDOKU).unwrap().peers(9, 0);
+    }
+
+    #[test]
+    fn neighbors_sharing_digit() {
+        let s = Sudoku::from_str(TEST_SUDOKU).unwrap();
+        assert_eq!(s.try_insert((5, 7), 8), Err(ColDuplicate));
+        assert_eq!(s.neighbors_sharing_digit(5, 7, 8), vec![(5, 3)]);
+        assert_eq!(
+            s.clone().with_diagonals().neighbors_sharing_digit(0, 0, 2),
+            vec![(4, 4)]
+        );
+        assert!(s.neighbors_sharing_digit(5, 7, 5).is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "each coordinate must be within range 0..9")]
+    fn neighbors_sharing_digit_out_of_range() {
+        Sudoku::from_str(TEST_SUDOKU)
+            .unwrap()
+            .neighbors_sharing_digit(0, 9, 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "number must be within range 1..=9")]
+    fn neighbors_sharing_digit_invalid_number() {
+        Sudoku::from_str(TEST_SUDOKU)
+            .unwrap()
+            .neighbors_sharing_digit(0, 0, 0);
+    }
+
+    #[test]
+    fn completed_units() {
+        let all: Vec<usize> = (0..9).collect();
+        assert_eq!(
+            Sudoku::from_str(SOLVED_SUDOKU).unwrap().completed_units(),
+            (all.clone(), all.clone(), all)
+        );
+        assert_eq!(
+            Sudoku::from_str(TEST_SUDOKU).unwrap().completed_units(),
+            (vec![], vec![], vec![])
+        );
+
+        let mut s = Sudoku::from_str(SOLVED_SUDOKU).unwrap();
+        s.xs[0] = None;
+        s.xs[80] = s.xs[79];
+        assert_eq!(
+            s.completed_units(),
+            (
+                vec![1, 2, 3, 4, 5, 6, 7],
+                vec![1, 2, 3, 4, 5, 6, 7],
+                vec![1, 2, 3, 4, 5, 6, 7]
+            )
+        );
+    }
+
+    #[test]
+    fn candidates_mask() {
+        let s = Sudoku::from_str(TEST_SUDOKU).unwrap();
+        for i in 0..81 {
+            let (x, y) = (i % 9, i / 9);
+            let mask = s.candidates_mask(x, y);
+            assert_eq!(mask >> 9, 0);
+            assert_eq!(
+                s.candidates(x, y),
+                (1..10)
+                    .filter(|num| mask & 1 << (num - 1) != 0)
+                    .collect::<Vec<u8>>()
+            );
+        }
+        assert_eq!(s.candidates_mask(7, 0), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "each coordinate must be within range 0..9")]
+    fn candidates_mask_out_of_range() {
+        Sudoku::from_str(TEST_SUDOKU).unwrap().candidates_mask(9, 0);
+    }
+
+    #[test]
+    fn total_candidates() {
+        assert_eq!(
+            Sudoku::from_str(SOLVED_SUDOKU).unwrap().total_candidates(),
+            0
+        );
+        assert_eq!(
+            Sudoku::from_str(&".".repeat(81))
+                .unwrap()
+                .total_candidates(),
+            81 * 9
+        );
+        let s = Sudoku::from_str(TEST_SUDOKU).unwrap();
+        let next = s.try_insert((0, 0), 1).unwrap();
+        assert!(next.total_candidates() < s.total_candidates());
+    }
+
+    #[test]
+    fn branching_factor() {
+        assert_eq!(
+            Sudoku::from_str(SOLVED_SUDOKU).unwrap().branching_factor(),
+            0.0
+        );
+        let s = Sudoku::from_str(TEST_SUDOKU).unwrap();
+        let empty = s.xs.iter().filter(|x| x.is_none()).count();
+        assert_eq!(
+            s.branching_factor(),
+            s.total_candidates() as f64 / empty as f64
+        );
+        assert!(s.branching_factor() > 1.0 && s.branching_factor() < 9.0);
+    }
+
+    #[test]
+    fn conflicts() {
+        assert!(Sudoku::from_str(SOLVED_SUDOKU)
+            .unwrap()
+            .conflicts()
+            .is_empty());
+
+        // The 5s share both a column and a block but are reported once.
+        let s = Sudoku::from_str(&format!("15.1......5.......{}", ".".repeat(63))).unwrap();
+        assert_eq!(s.conflicts(), vec![((0, 0), (3, 0)), ((1, 0), (1, 1))]);
+    }
+
+    #[test]
+    fn is_latin_square() {
+        assert!(Sudoku::from_str(SOLVED_SUDOKU).unwrap().is_latin_square());
+        assert!(Sudoku::from_str(TEST_SUDOKU).unwrap().is_latin_square());
+
+        // Two 1s sharing a block but neither a row nor a column.
+        let block_clash =
+            Sudoku::from_str(&format!("1{}1{}", ".".repeat(9), ".".repeat(70))).unwrap();
+        assert!(block_clash.is_latin_square());
+        assert!(!block_clash.is_valid());
+        let col_clash =
+            Sudoku::from_str(&format!("1{}1{}", ".".repeat(8), ".".repeat(71))).unwrap();
+        assert!(!col_clash.is_latin_square());
+    }
+
+    #[test]
+    fn to_line() {
+        let s = Sudoku::from_str(TEST_SUDOKU).unwrap();
+        assert_eq!(s.to_line(), TEST_SUDOKU.replace('x', "."));
+        assert_eq!(Sudoku::from_str(&s.to_line()).unwrap(), s);
+    }
+
+    #[test]
+    fn into_string() {
+        let s = Sudoku::from_str(TEST_SUDOKU).unwrap();
+        assert_eq!(String::from(&s), s.to_line());
+        let line: String = s.into();
+        assert_eq!(line, TEST_SUDOKU.replace('x', "."));
+    }
+
+    #[test]
+    fn blank_round_trip() {
+        let zeros = TEST_SUDOKU.replace('x', "0");
+        let s = Sudoku::from_str(&zeros).unwrap();
+        assert_eq!(s.blank(), '0');
+        assert_eq!(s.to_line_with(s.blank()), zeros);
+        assert_eq!(s.to_line(), TEST_SUDOKU.replace('x', "."));
+
+        let s = Sudoku::from_str(TEST_SUDOKU).unwrap();
+        assert_eq!(s.to_line_with(s.blank()), TEST_SUDOKU);
+        assert_eq!(Sudoku::from_str(SOLVED_SUDOKU).unwrap().blank(), '.');
+        assert_eq!(Sudoku::from_rows(vec![vec![0; 9]; 9]).unwrap().blank(), '.');
+    }
+
+    #[test]
+    fn box_values() {
+        let s = Sudoku::from_str(TEST_SUDOKU).unwrap();
+        let boxes = s.box_values();
+        assert_eq!(
+            boxes[0],
+            [None, None, None, None, Some(9), None, None, None, Some(4)]
+        );
+
+        let mut xs = [None; 81];
+        for (b, values) in boxes.iter().enumerate() {
+            for (i, &value) in values.iter().enumerate() {
+                let (x, y) = (b % 3 * 3 + i % 3, b / 3 * 3 + i / 3);
+                xs[y * 9 + x] = value.map(SudokuNum::Original);
+            }
+        }
+        assert_eq!(Sudoku::from_cells(xs), s);
+    }
+
+    #[test]
+    #[allow(clippy::bool_assert_comparison)]
     fn is_full() {
         assert_eq!(Sudoku::from_str(SOLVED_SUDOKU).unwrap().is_full(), true);
     }
 
     #[test]
+    #[allow(clippy::bool_assert_comparison)]
     fn isnt_full() {
         assert_eq!(Sudoku::from_str(TEST_SUDOKU).unwrap().is_full(), false);
     }
+
+    #[test]
+    fn zero_isnt_full() {
+        let zeros = SOLVED_SUDOKU.replacen('1', "0", 1);
+        assert!(!Sudoku::from_str(&zeros).unwrap().is_full());
+    }
+
+    #[test]
+    fn try_from() {
+        assert_eq!(
+            Sudoku::try_from(TEST_SUDOKU),
+            Ok(Sudoku::from_str(TEST_SUDOKU).unwrap())
+        );
+        assert_eq!(
+            Sudoku::try_from(format!("11{}", ".".repeat(79)).as_str()),
+            Err(ParseError::Conflict(Unit::Row(0)))
+        );
+        assert_eq!(Sudoku::try_from("123"), Err(ParseError::InvalidLength(3)));
+    }
+
+    #[test]
+    fn progress() {
+        let s = Sudoku::from_str(TEST_SUDOKU).unwrap();
+        assert_eq!(s.progress(), 23.0 / 81.0);
+        assert_eq!(s.edit_progress(), 0.0);
+        let s = s.try_insert((0, 0), 1).unwrap();
+        assert_eq!(s.progress(), 24.0 / 81.0);
+        assert_eq!(s.edit_progress(), 1.0 / 58.0);
+        assert_eq!(
+            Sudoku::from_str(SOLVED_SUDOKU).unwrap().edit_progress(),
+            1.0
+        );
+    }
+
+    #[test]
+    fn digit_counts() {
+        let s = Sudoku::from_str(TEST_SUDOKU).unwrap();
+        assert_eq!(s.digit_counts().iter().sum::<u8>(), 23);
+        let s = s.try_insert((0, 0), 1).unwrap();
+        assert_eq!(s.digit_counts()[0], 5);
+        assert_eq!(
+            Sudoku::from_str(SOLVED_SUDOKU).unwrap().digit_counts(),
+            [9; 9]
+        );
+    }
 }
@@ -0,0 +1,845 @@
+//! Human solving techniques, which report deductions about candidates without changing the grid.
+
+use crate::{solver, Sudoku, SudokuNum::Edited};
+use alloc::{format, string::String, vec::Vec};
+use core::time::Duration;
+
+/// A candidate `num` which a technique has shown cannot be placed at `loc: (x, y)`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Elimination {
+    pub loc: (usize, usize),
+    pub num: u8,
+}
+
+impl Elimination {
+    fn new(idx: usize, num: u8) -> Self {
+        Self {
+            loc: (idx % 9, idx / 9),
+            num,
+        }
+    }
+}
+
+/// The kind of the easiest deduction available in a grid, as returned by [`Sudoku::next_step_difficulty`]. Variants are ordered from easiest to hardest.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum StepDifficulty {
+    /// A cell has only one candidate.
+    NakedSingle,
+    /// A number has only one place in a row, column or block.
+    HiddenSingle,
+    /// Candidates can be eliminated with block and line intersections, as found by [`Sudoku::pointing`].
+    Pointing,
+    /// Candidates can be eliminated with hidden pairs, as found by [`Sudoku::hidden_pairs`].
+    HiddenPair,
+    /// Candidates can be eliminated with X-Wings, as found by [`Sudoku::x_wings`].
+    XWing,
+    /// No technique applies, so a digit must be guessed.
+    Guess,
+}
+
+impl StepDifficulty {
+    /// Every kind, from easiest to hardest.
+    const ALL: [Self; 6] = [
+        Self::NakedSingle,
+        Self::HiddenSingle,
+        Self::Pointing,
+        Self::HiddenPair,
+        Self::XWing,
+        Self::Guess,
+    ];
+
+    /// The weight of a step of this kind in [`Sudoku::difficulty_score`].
+    fn weight(self) -> u32 {
+        match self {
+            Self::NakedSingle => 1,
+            Self::HiddenSingle => 2,
+            Self::Pointing => 5,
+            Self::HiddenPair => 10,
+            Self::XWing => 20,
+            Self::Guess => 100,
+        }
+    }
+}
+
+/// How often each technique was used while solving with [`Sudoku::solve_profiled`], and how long was spent looking for it.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TechniqueProfile {
+    /// Uses of each technique, indexed by [`StepDifficulty`].
+    counts: [u32; 6],
+    /// Time spent looking for each technique, indexed by [`StepDifficulty`].
+    times: [Duration; 6],
+}
+
+impl TechniqueProfile {
+    /// Returns how many times `kind` was used. Singles and guesses each place one digit, while each use of an elimination technique is a round removing every candidate it rules out.
+    pub fn count(&self, kind: StepDifficulty) -> u32 {
+        self.counts[kind as usize]
+    }
+
+    /// Returns the total time spent looking for `kind`, including searches which found nothing. Times are only measured with the `std` feature, and are zero without it.
+    pub fn time(&self, kind: StepDifficulty) -> Duration {
+        self.times[kind as usize]
+    }
+
+    /// Runs `f`, adding the time it takes to that of `kind`.
+    fn timed<T>(&mut self, kind: StepDifficulty, f: impl FnOnce() -> T) -> T {
+        #[cfg(feature = "std")]
+        let start = std::time::Instant::now();
+        let result = f();
+        #[cfg(feature = "std")]
+        {
+            self.times[kind as usize] += start.elapsed();
+        }
+        #[cfg(not(feature = "std"))]
+        let _ = kind;
+        result
+    }
+}
+
+/// Candidate masks of every cell, indexed `y * 9 + x`, with bit `n - 1` set for each candidate `n`.
+pub(crate) type Masks = [u16; 81];
+
+impl Sudoku {
+    pub(crate) fn candidate_masks(&self) -> Masks {
+        let mut masks = [0; 81];
+        for (idx, mask) in masks.iter_mut().enumerate() {
+            *mask = self.candidate_mask(idx);
+        }
+        masks
+    }
+
+    /// Returns every empty cell `(x, y, num)` of `self` whose only candidate is `num`, in reading order. Nothing is filled, so cells whose single candidate comes from filling another are not included.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use newdoku::Sudoku;
+    /// use std::str::FromStr;
+    ///
+    /// let s = Sudoku::from_str(&format!(".23456789{}", ".".repeat(72))).unwrap();
+    /// assert_eq!(s.naked_singles(), vec![(0, 0, 1)]);
+    /// ```
+    pub fn naked_singles(&self) -> Vec<(usize, usize, u8)> {
+        (0..81)
+            .filter(|&idx| self.xs[idx].is_none())
+            .filter_map(|idx| {
+                let mask = self.candidate_mask(idx);
+                (mask.count_ones() == 1)
+                    .then(|| (idx % 9, idx / 9, mask.trailing_zeros() as u8 + 1))
+            })
+            .collect()
+    }
+
+    /// Returns the eliminations implied by X-Wings: a number whose candidates in two rows lie in the same two columns can be removed from the rest of those columns, and likewise with rows and columns swapped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use newdoku::Sudoku;
+    /// use std::str::FromStr;
+    ///
+    /// let s = Sudoku::from_str(
+    ///     "157832496396745218284196753415378962763429185928561374831257649672984531549613827",
+    /// ).unwrap();
+    /// assert!(s.x_wings().is_empty());
+    /// ```
+    pub fn x_wings(&self) -> Vec<Elimination> {
+        x_wings(&self.candidate_masks())
+    }
+
+    /// Returns the eliminations implied by intersections of blocks with rows and columns. If a number's candidates in a block all lie in one row or column, it can be removed from the rest of that line (pointing pairs and triples), and if its candidates in a row or column all lie in one block, it can be removed from the rest of that block (box-line reduction).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use newdoku::{Elimination, Sudoku};
+    /// use std::str::FromStr;
+    ///
+    /// // Filling the lower rows of the first block confines 1 to its top row.
+    /// let s = Sudoku::from_str(&format!("{}234......567......{}", ".".repeat(9), ".".repeat(54))).unwrap();
+    /// assert!(s.pointing().contains(&Elimination { loc: (8, 0), num: 1 }));
+    /// ```
+    pub fn pointing(&self) -> Vec<Elimination> {
+        pointing(&self.candidate_masks(), &self.houses())
+    }
+
+    /// Returns the eliminations implied by hidden pairs: if two numbers can only go in the same two cells of a row, column or block, every other candidate can be removed from those cells.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use newdoku::Sudoku;
+    /// use std::str::FromStr;
+    ///
+    /// let s = Sudoku::from_str(&".".repeat(81)).unwrap();
+    /// assert!(s.hidden_pairs().is_empty());
+    /// ```
+    pub fn hidden_pairs(&self) -> Vec<Elimination> {
+        hidden_pairs(&self.candidate_masks(), &self.houses())
+    }
+
+    /// Returns the steps `(x, y, num, reason)` of a walkthrough solving `self`, always taking the easiest available step and explaining it in `reason`. Returns an empty [`Vec`] if `self` has no solution.
+    ///
+    /// Naked singles are placed first, then hidden singles. When neither is available, candidates are eliminated with block and line intersections, hidden pairs and then X-Wings before looking for singles again. Only when every technique is stuck is a digit of the solution guessed, in the cell with the fewest candidates. Applying every step in order solves `self`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use newdoku::Sudoku;
+    /// use std::str::FromStr;
+    ///
+    /// let s = Sudoku::from_str(
+    ///     "157832496396745218284196753415378962763429185928561374831257649672984531549613.27",
+    /// ).unwrap();
+    /// let steps = s.guided_solution();
+    /// assert_eq!(steps.len(), 1);
+    /// let (x, y, num, reason) = &steps[0];
+    /// assert_eq!((*x, *y, *num), (6, 8, 8));
+    /// assert_eq!(reason, "naked single: 8 is the only candidate of R9C7");
+    /// ```
+    pub fn guided_solution(&self) -> Vec<(usize, usize, u8, String)> {
+        self.walkthrough(true, &mut TechniqueProfile::default())
+            .unwrap_or_default()
+    }
+
+    /// Returns true if `self` can be solved with the techniques of [`Sudoku::guided_solution`] alone, without ever guessing. A full valid grid is trivially solvable, while a grid with no solution or more than one is not.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use newdoku::Sudoku;
+    /// use std::str::FromStr;
+    ///
+    /// let s = Sudoku::from_str(
+    ///     "xxxxxxx9xx9x7xx21xxx4x9xxxxx1xxx8xxx7xx42xxx5xx8xxxx748x1xxxx4xxxxxxxxxxxx9613xxx",
+    /// ).unwrap();
+    /// assert_eq!(
+    ///     s.is_logic_solvable(),
+    ///     s.guided_solution().iter().all(|(.., reason)| !reason.starts_with("guess"))
+    /// );
+    /// assert!(!Sudoku::from_str(&".".repeat(81)).unwrap().is_logic_solvable());
+    /// ```
+    pub fn is_logic_solvable(&self) -> bool {
+        self.walkthrough(false, &mut TechniqueProfile::default())
+            .is_some()
+    }
+
+    /// Returns a rating of how hard `self` is to solve by hand, found by following the steps of [`Sudoku::guided_solution`] and adding up the weight of each, or [`None`] if `self` has no solution. Higher scores are harder, and a full grid scores 0.
+    ///
+    /// The weights are fixed, so a puzzle always gets the same score:
+    ///
+    /// | Step | Weight |
+    /// |:-|-:|
+    /// | naked single | 1 |
+    /// | hidden single | 2 |
+    /// | round of block and line intersections | 5 |
+    /// | round of hidden pairs | 10 |
+    /// | round of X-Wings | 20 |
+    /// | guess | 100 |
+    ///
+    /// A round is one application of a technique, removing every candidate it rules out at once.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use newdoku::Sudoku;
+    /// use std::str::FromStr;
+    ///
+    /// let s = Sudoku::from_str(
+    ///     "157832496396745218284196753415378962763429185928561374831257649672984531549613.27",
+    /// ).unwrap();
+    /// assert_eq!(s.difficulty_score(), Some(1));
+    /// let empty = Sudoku::from_str(&".".repeat(81)).unwrap();
+    /// assert!(empty.difficulty_score() > s.difficulty_score());
+    /// ```
+    pub fn difficulty_score(&self) -> Option<u32> {
+        let mut profile = TechniqueProfile::default();
+        self.walkthrough(true, &mut profile)?;
+        Some(
+            StepDifficulty::ALL
+                .iter()
+                .map(|&kind| kind.weight() * profile.count(kind))
+                .sum(),
+        )
+    }
+
+    /// Returns the kind of the easiest deduction which can be made in `self` next, trying each technique of [`Sudoku::guided_solution`] in turn, or [`None`] if `self` is full.
+    ///
+    /// Only the next step is looked at, without solving `self`, so a grid with no solution may still report a step.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use newdoku::{StepDifficulty, Sudoku};
+    /// use std::str::FromStr;
+    ///
+    /// let s = Sudoku::from_str(&format!(".23456789{}", ".".repeat(72))).unwrap();
+    /// assert_eq!(s.next_step_difficulty(), Some(StepDifficulty::NakedSingle));
+    /// let empty = Sudoku::from_str(&".".repeat(81)).unwrap();
+    /// assert_eq!(empty.next_step_difficulty(), Some(StepDifficulty::Guess));
+    /// ```
+    pub fn next_step_difficulty(&self) -> Option<StepDifficulty> {
+        if self.is_full() {
+            return None;
+        }
+        let masks = self.candidate_masks();
+        let houses = self.houses();
+        if naked_single(self, &masks).is_some() {
+            return Some(StepDifficulty::NakedSingle);
+        }
+        if hidden_single(self, &masks, &houses).is_some() {
+            return Some(StepDifficulty::HiddenSingle);
+        }
+        let steps = [
+            (StepDifficulty::Pointing, pointing(&masks, &houses)),
+            (StepDifficulty::HiddenPair, hidden_pairs(&masks, &houses)),
+            (StepDifficulty::XWing, x_wings(&masks)),
+        ];
+        Some(
+            steps
+                .into_iter()
+                .find(|(_, eliminations)| !eliminations.is_empty())
+                .map_or(StepDifficulty::Guess, |(kind, _)| kind),
+        )
+    }
+
+    /// Returns a solution of `self` found by following the steps of [`Sudoku::guided_solution`], with a profile of how often each technique was used and how long was spent on it, or [`None`] if `self` has no solution.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use newdoku::{StepDifficulty, Sudoku};
+    /// use std::str::FromStr;
+    ///
+    /// let s = Sudoku::from_str(
+    ///     "xxxxxxx9xx9x7xx21xxx4x9xxxxx1xxx8xxx7xx42xxx5xx8xxxx748x1xxxx4xxxxxxxxxxxx9613xxx",
+    /// ).unwrap();
+    /// let (solution, profile) = s.solve_profiled().unwrap();
+    /// assert!(solution.is_full() && solution.is_valid());
+    /// let placed: u32 = [StepDifficulty::NakedSingle, StepDifficulty::HiddenSingle, StepDifficulty::Guess]
+    ///     .into_iter()
+    ///     .map(|kind| profile.count(kind))
+    ///     .sum();
+    /// assert_eq!(placed, 81 - 23);
+    /// ```
+    pub fn solve_profiled(&self) -> Option<(Self, TechniqueProfile)> {
+        let mut profile = TechniqueProfile::default();
+        let mut solution = self.clone();
+        for (x, y, num, _) in self.walkthrough(true, &mut profile)? {
+            solution.xs[y * 9 + x] = Some(Edited(num));
+        }
+        Some((solution, profile))
+    }
+
+    /// Returns the steps of [`Sudoku::guided_solution`], or [`None`] if `self` has no solution or if a guess is needed and `guess` is false. Each step, and the time spent looking for each technique, is recorded in `profile`.
+    fn walkthrough(
+        &self,
+        guess: bool,
+        profile: &mut TechniqueProfile,
+    ) -> Option<Vec<(usize, usize, u8, String)>> {
+        /// Techniques which eliminate candidates, with the name used in reasons.
+        type Technique = (
+            &'static str,
+            StepDifficulty,
+            fn(&Masks, &Houses) -> Vec<Elimination>,
+        );
+        const ELIMINATIONS: [Technique; 3] = [
+            ("pointing", StepDifficulty::Pointing, pointing),
+            ("hidden pairs", StepDifficulty::HiddenPair, hidden_pairs),
+            ("X-Wings", StepDifficulty::XWing, |masks, _| x_wings(masks)),
+        ];
+
+        let solution = solver::solve(self, &mut Default::default())?;
+        let mut grid = self.clone();
+        let houses = self.houses();
+        // Candidates not yet ruled out by elimination techniques.
+        let mut allowed = [0x1ff; 81];
+        let mut techniques: Vec<&str> = Vec::new();
+        let mut steps = Vec::new();
+
+        while !grid.is_full() {
+            let mut masks = grid.candidate_masks();
+            for (mask, allowed) in masks.iter_mut().zip(allowed) {
+                *mask &= allowed;
+            }
+
+            let single = profile
+                .timed(StepDifficulty::NakedSingle, || naked_single(&grid, &masks))
+                .map(|step| (StepDifficulty::NakedSingle, step))
+                .or_else(|| {
+                    profile
+                        .timed(StepDifficulty::HiddenSingle, || {
+                            hidden_single(&grid, &masks, &houses)
+                        })
+                        .map(|step| (StepDifficulty::HiddenSingle, step))
+                });
+            let (kind, (idx, num, reason)) = match single {
+                Some(single) => single,
+                None => {
+                    let eliminations = ELIMINATIONS.iter().find_map(|&(technique, kind, find)| {
+                        let eliminations = profile.timed(kind, || find(&masks, &houses));
+                        (!eliminations.is_empty()).then_some((technique, kind, eliminations))
+                    });
+                    if let Some((technique, kind, eliminations)) = eliminations {
+                        profile.counts[kind as usize] += 1;
+                        for e in eliminations {
+                            allowed[e.loc.1 * 9 + e.loc.0] &= !(1 << (e.num - 1));
+                        }
+                        if !techniques.contains(&technique) {
+                            techniques.push(technique);
+                        }
+                        continue;
+                    }
+                    if !guess {
+                        return None;
+                    }
+
+                    let (idx, mask) = profile.timed(StepDifficulty::Guess, || {
+                        (0..81)
+                            .filter(|&idx| grid.xs[idx].is_none())
+                            .map(|idx| (idx, masks[idx]))
+                            .min_by_key(|&(_, mask)| mask.count_ones())
+                            .expect("a grid which is not full has an empty cell")
+                    });
+                    let num = solution.xs[idx].map_or(0, |num| num.value());
+                    let reason = format!(
+                        "guess: {} is one of {} candidates of {}",
+                        num,
+                        mask.count_ones(),
+                        cell_name(idx)
+                    );
+                    (StepDifficulty::Guess, (idx, num, reason))
+                }
+            };
+            profile.counts[kind as usize] += 1;
+
+            let reason = if techniques.is_empty() {
+                reason
+            } else {
+                format!(
+                    "{}, after eliminating with {}",
+                    reason,
+                    techniques.join(" and ")
+                )
+            };
+            techniques.clear();
+            grid.xs[idx] = Some(Edited(num));
+            steps.push((idx % 9, idx / 9, num, reason));
+        }
+        Some(steps)
+    }
+}
+
+/// Returns the name of the cell at `idx` in row and column notation counting from 1, e.g. `R3C5`.
+fn cell_name(idx: usize) -> String {
+    format!("R{}C{}", idx / 9 + 1, idx % 9 + 1)
+}
+
+/// Cell indices of each row, column and block, in that order, as returned by [`Sudoku::houses`].
+pub(crate) type Houses = [[usize; 9]; 27];
+
+/// Returns a naked single of `grid` as the cell index, digit and reason.
+fn naked_single(grid: &Sudoku, masks: &Masks) -> Option<(usize, u8, String)> {
+    let idx = (0..81).find(|&idx| grid.xs[idx].is_none() && masks[idx].count_ones() == 1)?;
+    let num = masks[idx].trailing_zeros() as u8 + 1;
+    let reason = format!(
+        "naked single: {} is the only candidate of {}",
+        num,
+        cell_name(idx)
+    );
+    Some((idx, num, reason))
+}
+
+/// Returns a hidden single of `grid` as the cell index, digit and reason.
+fn hidden_single(grid: &Sudoku, masks: &Masks, houses: &Houses) -> Option<(usize, u8, String)> {
+    for (u, unit) in houses.iter().enumerate() {
+        for num in 1..10 {
+            let bit = 1 << (num - 1);
+            let mut cells = unit
+                .iter()
+                .filter(|&&idx| grid.xs[idx].is_none() && masks[idx] & bit != 0);
+            if let (Some(&idx), None) = (cells.next(), cells.next()) {
+                let kind = ["row", "column", "block"][u / 9];
+                let reason = format!(
+                    "hidden single: {} is the only place for {} in {} {}",
+                    cell_name(idx),
+                    num,
+                    kind,
+                    u % 9 + 1
+                );
+                return Some((idx, num, reason));
+            }
+        }
+    }
+    None
+}
+
+/// Sorts `eliminations` and removes duplicates.
+fn dedup(mut eliminations: Vec<Elimination>) -> Vec<Elimination> {
+    eliminations.sort_unstable();
+    eliminations.dedup();
+    eliminations
+}
+
+pub(crate) fn x_wings(masks: &Masks) -> Vec<Elimination> {
+    let mut eliminations = Vec::new();
+    // Each orientation maps (line, position along the line) to a cell index.
+    let orientations: [fn(usize, usize) -> usize; 2] = [|l, p| l * 9 + p, |l, p| p * 9 + l];
+
+    for cell in orientations {
+        for num in 1..10 {
+            let bit = 1 << (num - 1);
+            let pairs: Vec<(usize, [usize; 2])> = (0..9)
+                .filter_map(|line| {
+                    let positions: Vec<usize> = (0..9)
+                        .filter(|&p| masks[cell(line, p)] & bit != 0)
+                        .collect();
+                    match positions[..] {
+                        [a, b] => Some((line, [a, b])),
+                        _ => None,
+                    }
+                })
+                .collect();
+
+            for (i, &(first, positions)) in pairs.iter().enumerate() {
+                for &(second, _) in pairs[i + 1..].iter().filter(|(_, p)| *p == positions) {
+                    for line in (0..9).filter(|&l| l != first && l != second) {
+                        for p in positions {
+                            if masks[cell(line, p)] & bit != 0 {
+                                eliminations.push(Elimination::new(cell(line, p), num));
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+    dedup(eliminations)
+}
+
+pub(crate) fn pointing(masks: &Masks, houses: &Houses) -> Vec<Elimination> {
+    let mut eliminations = Vec::new();
+    let (lines, blocks) = houses.split_at(18);
+    for block in blocks {
+        for line in lines {
+            // Cells of the line outside the block and cells of the block outside the line.
+            let (line_rest, block_rest): (Vec<usize>, Vec<usize>) = (
+                line.iter()
+                    .copied()
+                    .filter(|idx| !block.contains(idx))
+                    .collect(),
+                block
+                    .iter()
+                    .copied()
+                    .filter(|idx| !line.contains(idx))
+                    .collect(),
+            );
+            if line_rest.len() == 9 {
+                continue;
+            }
+
+            for num in 1..10 {
+                let bit = 1 << (num - 1);
+                let has = |cells: &[usize]| cells.iter().any(|&idx| masks[idx] & bit != 0);
+                let in_both = line
+                    .iter()
+                    .any(|idx| block.contains(idx) && masks[*idx] & bit != 0);
+                if !in_both {
+                    continue;
+                }
+                for (confined, rest) in [(&block_rest, &line_rest), (&line_rest, &block_rest)] {
+                    if !has(confined) {
+                        for &idx in rest.iter().filter(|&&idx| masks[idx] & bit != 0) {
+                            eliminations.push(Elimination::new(idx, num));
+                        }
+                    }
+                }
+            }
+        }
+    }
+    dedup(eliminations)
+}
+
+pub(crate) fn hidden_pairs(masks: &Masks, houses: &Houses) -> Vec<Elimination> {
+    let mut eliminations = Vec::new();
+    for unit in houses {
+        // Positions within the unit where each number is a candidate, with bit `i` set for `unit[i]`.
+        let mut positions = [0u16; 9];
+        for (i, &idx) in unit.iter().enumerate() {
+            for (n, positions) in positions.iter_mut().enumerate() {
+                if masks[idx] & 1 << n != 0 {
+                    *positions |= 1 << i;
+                }
+            }
+        }
+
+        for a in 0..9 {
+            if positions[a].count_ones() != 2 {
+                continue;
+            }
+            for b in (a + 1..9).filter(|&b| positions[b] == positions[a]) {
+                let pair = 1 << a | 1 << b;
+                for (i, &idx) in unit.iter().enumerate() {
+                    if positions[a] & 1 << i == 0 {
+                        continue;
+                    }
+                    for n in (0..9).filter(|n| masks[idx] & !pair & 1 << n != 0) {
+                        eliminations.push(Elimination::new(idx, n as u8 + 1));
+                    }
+                }
+            }
+        }
+    }
+    dedup(eliminations)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::UNITS;
+    use std::str::FromStr;
+
+    #[test]
+    fn x_wing_rows() {
+        let mut masks = [0x1ff; 81];
+        for x in (0..9).filter(|&x| x != 2 && x != 5) {
+            masks[x] &= !1;
+            masks[4 * 9 + x] &= !1;
+        }
+
+        let eliminations = x_wings(&masks);
+        assert_eq!(eliminations.len(), 14);
+        assert!(eliminations
+            .iter()
+            .all(|e| e.num == 1 && (e.loc.0 == 2 || e.loc.0 == 5) && e.loc.1 != 0 && e.loc.1 != 4));
+    }
+
+    #[test]
+    fn pointing_pair() {
+        let mut masks = [0x1ff; 81];
+        // Confine 5 within block 1 to (0, 1) and (2, 1).
+        for idx in [0, 1, 2, 10, 18, 19, 20] {
+            masks[idx] &= !(1 << 4);
+        }
+
+        let eliminations = pointing(&masks, &UNITS);
+        assert_eq!(eliminations.len(), 6);
+        assert!(eliminations
+            .iter()
+            .all(|e| e.num == 5 && e.loc.1 == 1 && e.loc.0 > 2));
+    }
+
+    #[test]
+    fn box_line_reduction() {
+        let mut masks = [0x1ff; 81];
+        // Confine 3 within column 5 to the middle block.
+        for y in (0..9).filter(|&y| !(3..6).contains(&y)) {
+            masks[y * 9 + 4] &= !(1 << 2);
+        }
+
+        let eliminations = pointing(&masks, &UNITS);
+        assert_eq!(eliminations.len(), 6);
+        assert!(eliminations
+            .iter()
+            .all(|e| e.num == 3 && (3..6).contains(&e.loc.1) && e.loc.0 != 4));
+    }
+
+    #[test]
+    fn hidden_pair() {
+        let mut masks = [0x1ff; 81];
+        // Confine 1 and 2 to (0, 3) and (4, 3) within row 3.
+        for x in (0..9).filter(|&x| x != 0 && x != 4) {
+            masks[3 * 9 + x] &= !0b11;
+        }
+
+        let eliminations = hidden_pairs(&masks, &UNITS);
+        assert_eq!(eliminations.len(), 14);
+        assert!(eliminations
+            .iter()
+            .all(|e| e.num > 2 && e.loc.1 == 3 && (e.loc.0 == 0 || e.loc.0 == 4)));
+
+        // A third cell holding 1 breaks the pair.
+        masks[3 * 9 + 8] |= 1;
+        assert!(hidden_pairs(&masks, &UNITS).is_empty());
+    }
+
+    #[test]
+    fn guided_solution() {
+        let s = Sudoku::from_str(
+            "xxxxxxx9xx9x7xx21xxx4x9xxxxx1xxx8xxx7xx42xxx5xx8xxxx748x1xxxx4xxxxxxxxxxxx9613xxx",
+        )
+        .unwrap();
+        let steps = s.guided_solution();
+        assert_eq!(steps.len(), 58);
+
+        let mut grid = s.clone();
+        for (x, y, num, reason) in &steps {
+            assert!(!reason.is_empty());
+            grid = grid.try_insert((*x, *y), *num).unwrap();
+        }
+        assert_eq!(grid, solver::solve(&s, &mut Default::default()).unwrap());
+
+        // An empty grid needs guesses, but the walkthrough still ends solved.
+        let empty = Sudoku::from_str(&".".repeat(81)).unwrap();
+        let steps = empty.guided_solution();
+        assert!(steps[0].3.starts_with("guess: "));
+        assert_eq!(steps.len(), 81);
+
+        let unsolvable = Sudoku::from_str(&format!("11{}", ".".repeat(79))).unwrap();
+        assert!(unsolvable.guided_solution().is_empty());
+    }
+
+    #[test]
+    fn is_logic_solvable() {
+        let solved =
+            "157832496396745218284196753415378962763429185928561374831257649672984531549613827";
+        assert!(Sudoku::from_str(solved).unwrap().is_logic_solvable());
+        let easy = Sudoku::from_str(&solved.replace('5', ".")).unwrap();
+        assert!(easy.is_logic_solvable());
+
+        let s = Sudoku::from_str(
+            "xxxxxxx9xx9x7xx21xxx4x9xxxxx1xxx8xxx7xx42xxx5xx8xxxx748x1xxxx4xxxxxxxxxxxx9613xxx",
+        )
+        .unwrap();
+        let guesses = s
+            .guided_solution()
+            .iter()
+            .any(|(.., reason)| reason.starts_with("guess: "));
+        assert_eq!(s.is_logic_solvable(), !guesses);
+
+        assert!(!Sudoku::from_str(&".".repeat(81))
+            .unwrap()
+            .is_logic_solvable());
+        let unsolvable = Sudoku::from_str(&format!("11{}", ".".repeat(79))).unwrap();
+        assert!(!unsolvable.is_logic_solvable());
+    }
+
+    #[test]
+    fn naked_singles() {
+        let solved =
+            "157832496396745218284196753415378962763429185928561374831257649672984531549613827";
+        let s = Sudoku::from_str(&format!("..{}.", &solved[2..80])).unwrap();
+        assert_eq!(s.naked_singles(), vec![(0, 0, 1), (1, 0, 5), (8, 8, 7)]);
+        assert!(Sudoku::from_str(solved).unwrap().naked_singles().is_empty());
+        assert!(Sudoku::from_str(&".".repeat(81))
+            .unwrap()
+            .naked_singles()
+            .is_empty());
+    }
+
+    #[test]
+    fn difficulty_score() {
+        let solved =
+            "157832496396745218284196753415378962763429185928561374831257649672984531549613827";
+        assert_eq!(
+            Sudoku::from_str(solved).unwrap().difficulty_score(),
+            Some(0)
+        );
+        let singles = Sudoku::from_str(&solved.replace('5', ".")).unwrap();
+        assert_eq!(singles.difficulty_score(), Some(9));
+
+        let s = Sudoku::from_str(
+            "xxxxxxx9xx9x7xx21xxx4x9xxxxx1xxx8xxx7xx42xxx5xx8xxxx748x1xxxx4xxxxxxxxxxxx9613xxx",
+        )
+        .unwrap();
+        let score = s.difficulty_score().unwrap();
+        assert_eq!(score, s.difficulty_score().unwrap());
+        assert!(score > 9);
+        let unsolvable = Sudoku::from_str(&format!("11{}", ".".repeat(79))).unwrap();
+        assert_eq!(unsolvable.difficulty_score(), None);
+    }
+
+    #[test]
+    fn next_step_difficulty() {
+        let solved =
+            "157832496396745218284196753415378962763429185928561374831257649672984531549613827";
+        assert_eq!(
+            Sudoku::from_str(solved).unwrap().next_step_difficulty(),
+            None
+        );
+
+        // 1 is ruled out of row 1 everywhere but R1C9, which has many candidates.
+        let mut hidden = Sudoku::from_str(&".".repeat(81)).unwrap();
+        for (x, y) in [(1, 1), (4, 2), (6, 4), (7, 8)] {
+            hidden.with_given(x, y, 1).unwrap();
+        }
+        assert_eq!(
+            hidden.next_step_difficulty(),
+            Some(StepDifficulty::HiddenSingle)
+        );
+
+        let pointing = Sudoku::from_str(&format!(
+            "{}234......567......{}",
+            ".".repeat(9),
+            ".".repeat(54)
+        ))
+        .unwrap();
+        assert_eq!(
+            pointing.next_step_difficulty(),
+            Some(StepDifficulty::Pointing)
+        );
+    }
+
+    #[test]
+    fn solve_profiled() {
+        let s = Sudoku::from_str(
+            "xxxxxxx9xx9x7xx21xxx4x9xxxxx1xxx8xxx7xx42xxx5xx8xxxx748x1xxxx4xxxxxxxxxxxx9613xxx",
+        )
+        .unwrap();
+        let (solution, profile) = s.solve_profiled().unwrap();
+        assert_eq!(solution, s.solve_with(crate::Strategy::Propagate).unwrap());
+        let steps = s.guided_solution();
+        let count =
+            |prefix: &str| steps.iter().filter(|(.., r)| r.starts_with(prefix)).count() as u32;
+        assert_eq!(
+            profile.count(StepDifficulty::NakedSingle),
+            count("naked single")
+        );
+        assert_eq!(
+            profile.count(StepDifficulty::HiddenSingle),
+            count("hidden single")
+        );
+        assert_eq!(profile.count(StepDifficulty::Guess), count("guess"));
+        assert!(profile.time(StepDifficulty::NakedSingle) > Duration::ZERO);
+
+        let unsolvable = Sudoku::from_str(&format!("11{}", ".".repeat(79))).unwrap();
+        assert_eq!(unsolvable.solve_profiled(), None);
+    }
+
+    #[test]
+    fn single_reasons() {
+        let s = Sudoku::from_str(&format!(".23456789{}", ".".repeat(72))).unwrap();
+        let (idx, num, reason) = naked_single(&s, &s.candidate_masks()).unwrap();
+        assert_eq!((idx, num), (0, 1));
+        assert_eq!(reason, "naked single: 1 is the only candidate of R1C1");
+
+        // The other 1s leave (0, 0) as the only place for 1 in row 1, though it has other candidates.
+        let mut line = [b'.'; 81];
+        for (x, y) in [(3, 1), (6, 2), (1, 5), (2, 7)] {
+            line[y * 9 + x] = b'1';
+        }
+        let s = Sudoku::from_str(core::str::from_utf8(&line).unwrap()).unwrap();
+        assert_eq!(naked_single(&s, &s.candidate_masks()), None);
+        let (_, num, reason) = hidden_single(&s, &s.candidate_masks(), &UNITS).unwrap();
+        assert_eq!(num, 1);
+        assert_eq!(
+            reason,
+            "hidden single: R1C1 is the only place for 1 in row 1"
+        );
+    }
+
+    #[test]
+    fn x_wing_cols() {
+        let mut masks = [0x1ff; 81];
+        for y in (0..9).filter(|&y| y != 1 && y != 7) {
+            masks[y * 9 + 3] &= !(1 << 8);
+            masks[y * 9 + 6] &= !(1 << 8);
+        }
+
+        let eliminations = x_wings(&masks);
+        assert_eq!(eliminations.len(), 14);
+        assert!(eliminations
+            .iter()
+            .all(|e| e.num == 9 && (e.loc.1 == 1 || e.loc.1 == 7)));
+    }
+}
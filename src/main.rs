@@ -1,10 +1,24 @@
 use crossterm::{
     cursor,
+    event::{self, Event, KeyCode, KeyEvent, KeyModifiers},
+    execute, queue,
     style::{Attribute, Color, SetForegroundColor},
-    terminal::{Clear, ClearType::CurrentLine},
+    terminal::{
+        self, Clear,
+        ClearType::{All, CurrentLine},
+        EnterAlternateScreen, LeaveAlternateScreen,
+    },
+};
+use newdoku::{
+    clap::{ArgEnum, Parser},
+    Border, NoSolution, ParseError, Strategy, Sudoku, SudokuGame, Theme, UniqueResult,
+};
+use std::{
+    env, fmt, fs,
+    io::{self, IsTerminal, Write},
+    process,
+    time::{Duration, Instant},
 };
-use newdoku::{clap::Parser, Sudoku};
-use std::{fs, process, str::FromStr};
 
 #[derive(Parser, Debug)]
 struct Config {
@@ -16,60 +30,591 @@ struct Config {
     #[clap(short, long)]
     quiet: bool,
 
-    /// Load Sudoku by unique ID
+    /// Load Sudoku by unique ID, optionally prefixed with its scheme (line: or b64:)
     #[clap(short, long)]
     uid: Option<String>,
 
     /// Load Sudoku from file
     #[clap(short, long)]
     file: Option<String>,
+
+    /// Write the solution to OUTPUT without ANSI escapes
+    #[clap(short, long)]
+    output: Option<String>,
+
+    /// Format of the solution written to OUTPUT
+    #[clap(long, arg_enum, default_value = "line")]
+    format: Format,
+
+    /// Refuse to solve a Sudoku with more than one solution
+    #[clap(long)]
+    require_unique: bool,
+
+    /// Play the Sudoku in the terminal instead of solving it
+    #[clap(short, long)]
+    interactive: bool,
+
+    /// Solve every line of the input and print one JSON object per line
+    #[clap(long)]
+    jsonl: bool,
+
+    /// Solve every line of the input and print the solutions side by side
+    #[clap(long)]
+    batch: bool,
+
+    /// Check that every line of the input has exactly one solution
+    #[clap(long)]
+    validate_only: bool,
+
+    /// Print each puzzle of the input unless it is equivalent to an earlier one
+    #[clap(long)]
+    dedup: bool,
+
+    /// Draw grid borders with ASCII or Unicode box-drawing characters [default: unicode on a UTF-8 terminal outside CJK locales, else ascii]
+    #[clap(long, arg_enum, value_name = "STYLE")]
+    border: Option<BorderStyle>,
+
+    /// Solve N times without output and report the solve time to stderr
+    #[clap(long, value_name = "N")]
+    repeat: Option<u32>,
 }
 
-fn main() {
-    let config = Config::parse();
+/// A format for the solution written by `--output`.
+#[derive(ArgEnum, Clone, Copy, Debug)]
+enum Format {
+    /// A single line of 81 digits.
+    Line,
+    /// Nine lines of nine digits.
+    Rows,
+    /// Comma-separated values, one row per line.
+    Csv,
+    /// A markdown table.
+    Markdown,
+}
 
-    let sudoku = match &config.file {
-        Some(file) => Sudoku::from_str(&fs::read_to_string(file).unwrap_or_else(|e| {
-            eprintln!("Could not read file `{}`: {}", file, e);
-            process::exit(1);
-        })),
-        _ => match &config.uid {
-            Some(uid) => Sudoku::from_str(uid),
-            _ => Sudoku::from_str(
-                "xxxxxxx9xx9x7xx21xxx4x9xxxxx1xxx8xxx7xx42xxx5xx8xxxx748x1xxxx4xxxxxxxxxxxx9613xxx",
+impl Format {
+    /// Returns `sudoku` written in this format, ending with a newline.
+    fn write(self, sudoku: &Sudoku) -> String {
+        match self {
+            Self::Line => sudoku.to_line() + "\n",
+            Self::Rows => sudoku.line_with_boxes(),
+            Self::Csv => sudoku.to_csv(),
+            Self::Markdown => sudoku.to_markdown(),
+        }
+    }
+}
+
+/// The characters grid borders are drawn with, chosen by `--border`.
+#[derive(ArgEnum, Clone, Copy, Debug)]
+enum BorderStyle {
+    /// `+`, `-` and `|`.
+    Ascii,
+    /// Box-drawing characters such as `┼`.
+    Unicode,
+}
+
+impl BorderStyle {
+    /// Returns the border style to use when `--border` is not given: Unicode if stdout is a terminal and the locale suits it by [`BorderStyle::for_locale`], and ASCII if output is piped.
+    fn detect() -> Self {
+        let locale = ["LC_ALL", "LC_CTYPE", "LANG"]
+            .iter()
+            .find_map(|var| env::var(var).ok().filter(|value| !value.is_empty()))
+            .unwrap_or_default();
+        match io::stdout().is_terminal() {
+            true => Self::for_locale(&locale),
+            false => Self::Ascii,
+        }
+    }
+
+    /// Returns Unicode if `locale` uses UTF-8 and is not Chinese, Japanese or Korean, whose terminals draw box-drawing characters two columns wide and so misalign the grid, and ASCII otherwise.
+    fn for_locale(locale: &str) -> Self {
+        let locale = locale.to_ascii_lowercase();
+        let utf8 = locale.contains("utf-8") || locale.contains("utf8");
+        let cjk = ["zh", "ja", "ko"]
+            .iter()
+            .any(|lang| locale.starts_with(lang));
+        match utf8 && !cjk {
+            true => Self::Unicode,
+            false => Self::Ascii,
+        }
+    }
+
+    fn border(self) -> Border {
+        match self {
+            Self::Ascii => Border::Ascii,
+            Self::Unicode => Border::Unicode,
+        }
+    }
+}
+
+/// Decodes the payload of a `--uid` into the puzzle as a line.
+type UidDecoder = fn(&str) -> Result<String, ParseError>;
+
+/// Decoders for each `scheme:` prefix accepted by `--uid`.
+const UID_SCHEMES: &[(&str, UidDecoder)] = &[
+    ("line", |payload| Ok(String::from(payload))),
+    ("b64", |payload| {
+        Sudoku::from_base64(payload).map(|s| s.to_line())
+    }),
+];
+
+/// Returns the puzzle line of `uid`, decoding it with the scheme named by its prefix. A uid whose text before the first `:` names no scheme in [`UID_SCHEMES`] is read as a line, since `:` may stand for a blank.
+fn decode_uid(uid: &str) -> Result<String, CliError> {
+    let scheme = uid.split_once(':').and_then(|(scheme, payload)| {
+        UID_SCHEMES
+            .iter()
+            .find(|(name, _)| *name == scheme)
+            .map(|(_, decode)| (decode, payload))
+    });
+    match scheme {
+        Some((decode, payload)) => decode(payload).map_err(CliError::Parse),
+        None => Ok(String::from(uid)),
+    }
+}
+
+const DEFAULT_SUDOKU: &str =
+    "xxxxxxx9xx9x7xx21xxx4x9xxxxx1xxx8xxx7xx42xxx5xx8xxxx748x1xxxx4xxxxxxxxxxxx9613xxx";
+
+/// Returns `s` as a quoted JSON string.
+fn json_string(s: &str) -> String {
+    let mut json = String::from('"');
+    for c in s.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            c if c.is_control() => json.push_str(&format!("\\u{:04x}", c as u32)),
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+    json
+}
+
+/// Solves each puzzle line of `src` as read by [`Sudoku::parse_lines`], skipping blank lines and `#` comments, and prints one JSON record per puzzle. A line which cannot be parsed or has no solution gets a `null` solution and an `error` describing why.
+fn print_jsonl(src: &str) -> Result<(), CliError> {
+    let (mut invalid, mut unsolved) = (0, 0);
+    let lines: Vec<&str> = src.lines().collect();
+    for (line, result) in Sudoku::parse_lines(src) {
+        let start = Instant::now();
+        let result = match result {
+            Ok(sudoku) => sudoku.solve_with(Strategy::Propagate).ok_or_else(|| {
+                unsolved += 1;
+                NoSolution.to_string()
+            }),
+            // The record already holds the input, so the error leaves out its line number.
+            Err(ParseError::Line { source, .. }) => {
+                invalid += 1;
+                Err(format!("could not parse sudoku: {}", source))
+            }
+            Err(e) => {
+                invalid += 1;
+                Err(format!("could not parse sudoku: {}", e))
+            }
+        };
+        let time = start.elapsed();
+
+        let (solution, error) = match result {
+            Ok(solution) => (json_string(&solution.to_line()), String::from("null")),
+            Err(e) => (String::from("null"), json_string(&e)),
+        };
+        println!(
+            "{{\"input\":{},\"solution\":{},\"error\":{},\"time_ms\":{}}}",
+            json_string(lines[line - 1].trim()),
+            solution,
+            error,
+            time.as_secs_f64() * 1000.0
+        );
+    }
+
+    match (invalid, unsolved) {
+        (0, 0) => Ok(()),
+        (invalid, unsolved) => Err(CliError::Batch { invalid, unsolved }),
+    }
+}
+
+/// Solves each puzzle line of `src` and prints the solutions side by side using `theme`, as many to a row as fit in the terminal, or one per row if its width is unknown. Lines which cannot be parsed or have no solution are reported to stderr.
+fn print_batch(src: &str, theme: &Theme) -> Result<(), CliError> {
+    let (mut invalid, mut unsolved) = (0, 0);
+    let mut solutions = Vec::new();
+    for (line, result) in Sudoku::parse_lines(src) {
+        match result.map(|sudoku| sudoku.solve_with(Strategy::Propagate)) {
+            Ok(Some(solution)) => solutions.push(solution),
+            Ok(None) => {
+                unsolved += 1;
+                eprintln!("line {}: {}", line, NoSolution);
+            }
+            Err(e) => {
+                invalid += 1;
+                eprintln!("{}", e);
+            }
+        }
+    }
+
+    let width = terminal::size().map_or(0, |(cols, _)| cols as usize);
+    if !solutions.is_empty() {
+        println!("{}", Sudoku::display_beside(&solutions, theme, width));
+    }
+    match (invalid, unsolved) {
+        (0, 0) => Ok(()),
+        (invalid, unsolved) => Err(CliError::Batch { invalid, unsolved }),
+    }
+}
+
+/// Checks that each puzzle line of `src` has exactly one solution, reporting the line numbers of bad puzzles to stderr and then printing a summary.
+fn validate(src: &str) -> Result<(), CliError> {
+    let (mut valid, mut multiple, mut invalid) = (0, 0, 0);
+    for (line, result) in Sudoku::parse_lines(src) {
+        match result.map(|sudoku| sudoku.count_solutions(2)) {
+            Ok(1) => valid += 1,
+            Ok(0) => {
+                invalid += 1;
+                eprintln!("line {}: {}", line, NoSolution);
+            }
+            Ok(_) => {
+                multiple += 1;
+                eprintln!("line {}: sudoku has more than one solution", line);
+            }
+            Err(e) => {
+                invalid += 1;
+                eprintln!("{}", e);
+            }
+        }
+    }
+
+    println!(
+        "{} valid, {} multiple, {} invalid",
+        valid, multiple, invalid
+    );
+    match multiple + invalid {
+        0 => Ok(()),
+        bad => Err(CliError::Invalid(bad)),
+    }
+}
+
+/// Prints each puzzle line of `src` which is not equivalent to an earlier one, reporting lines which cannot be parsed and the number of duplicates removed to stderr.
+fn dedup(src: &str) -> Result<(), CliError> {
+    let mut invalid = 0;
+    let puzzles: Vec<_> = Sudoku::parse_lines(src)
+        .filter_map(|(_, result)| {
+            result
+                .map_err(|e| {
+                    invalid += 1;
+                    eprintln!("{}", e);
+                })
+                .ok()
+        })
+        .collect();
+    let total = puzzles.len();
+    let unique = Sudoku::dedup(puzzles);
+    for sudoku in &unique {
+        println!("{}", sudoku.to_line());
+    }
+
+    eprintln!("Removed {} duplicates", total - unique.len());
+    match invalid {
+        0 => Ok(()),
+        bad => Err(CliError::Invalid(bad)),
+    }
+}
+
+/// Solves `sudoku` quietly `times` times, reporting the total and average solve time to stderr.
+fn benchmark(sudoku: &Sudoku, times: u32) -> Result<(), CliError> {
+    let mut total = Duration::ZERO;
+    for _ in 0..times {
+        let start = Instant::now();
+        sudoku.solution(0, true).ok_or(CliError::NoSolution)?;
+        total += start.elapsed();
+    }
+    eprintln!(
+        "Solved {} times in {:.3}ms ({:.3}ms each)",
+        times,
+        total.as_secs_f64() * 1000.0,
+        total.as_secs_f64() * 1000.0 / times.max(1) as f64
+    );
+    Ok(())
+}
+
+const PLAY_HELP: &str = "Arrows move, 1-9 place, 0 or Backspace clear, u undo, q quit";
+
+/// Draws the board of `game` using `theme` with `status` beneath it, leaving the terminal cursor on the cell `(x, y)`.
+fn draw(
+    stdout: &mut io::Stdout,
+    game: &SudokuGame,
+    theme: &Theme,
+    (x, y): (usize, usize),
+    status: &str,
+) -> io::Result<()> {
+    queue!(stdout, Clear(All), cursor::MoveTo(0, 0))?;
+    write!(
+        stdout,
+        "{}\r\n{}",
+        game.board()
+            .display_with(theme)
+            .to_string()
+            .replace('\n', "\r\n"),
+        status
+    )?;
+    queue!(
+        stdout,
+        cursor::MoveTo((2 + x * 2 + x / 3 * 2) as u16, (1 + y + y / 3) as u16)
+    )?;
+    stdout.flush()
+}
+
+/// Runs the key loop of [`play`] until the player quits or solves `game`, drawing it using `theme`. Returns true if it was solved.
+fn play_loop(stdout: &mut io::Stdout, game: &mut SudokuGame, theme: &Theme) -> io::Result<bool> {
+    let (mut x, mut y) = (0, 0);
+    let mut status = String::from(PLAY_HELP);
+    loop {
+        draw(stdout, game, theme, (x, y), &status)?;
+        let (code, modifiers) = match event::read()? {
+            Event::Key(KeyEvent { code, modifiers }) => (code, modifiers),
+            _ => continue,
+        };
+
+        status = String::from(PLAY_HELP);
+        match code {
+            KeyCode::Char('c') if modifiers.contains(KeyModifiers::CONTROL) => return Ok(false),
+            KeyCode::Char('q') | KeyCode::Esc => return Ok(false),
+            KeyCode::Left | KeyCode::Char('h') => x = (x + 8) % 9,
+            KeyCode::Right | KeyCode::Char('l') => x = (x + 1) % 9,
+            KeyCode::Up | KeyCode::Char('k') => y = (y + 8) % 9,
+            KeyCode::Down | KeyCode::Char('j') => y = (y + 1) % 9,
+            KeyCode::Char(c @ '1'..='9') => {
+                if let Err(e) = game.insert((x, y), c as u8 - b'0') {
+                    status = format!(
+                        "{}Cannot place {}: {}{}",
+                        SetForegroundColor(Color::Red),
+                        c,
+                        e,
+                        Attribute::Reset
+                    );
+                }
+            }
+            KeyCode::Char('0' | '.') | KeyCode::Backspace | KeyCode::Delete => game
+                .clear(x, y)
+                .expect("the cursor is always within the grid"),
+            KeyCode::Char('u') if !game.undo() => status = String::from("Nothing to undo"),
+            _ => {}
+        }
+
+        if game.is_solved() {
+            return Ok(true);
+        }
+    }
+}
+
+/// Lets the player solve `game` in the terminal drawn using `theme`, restoring the terminal afterwards. Returns true if it was solved.
+fn play(game: &mut SudokuGame, theme: &Theme) -> io::Result<bool> {
+    let mut stdout = io::stdout();
+    terminal::enable_raw_mode()?;
+    execute!(stdout, EnterAlternateScreen)?;
+    let solved = play_loop(&mut stdout, game, theme);
+    execute!(stdout, LeaveAlternateScreen)?;
+    terminal::disable_raw_mode()?;
+    solved
+}
+
+/// An error which ends the program, each kind with its own exit code.
+#[derive(Debug)]
+enum CliError {
+    /// The sudoku has no solution.
+    NoSolution,
+    /// The sudoku has more than one solution but `--require-unique` was given.
+    NotUnique,
+    /// The input could not be parsed as a sudoku.
+    Parse(ParseError),
+    /// Some number of puzzles failed `--validate-only`, or could not be parsed by `--dedup`.
+    Invalid(usize),
+    /// Some lines of a `--jsonl` or `--batch` input could not be parsed or had no solution.
+    Batch { invalid: usize, unsolved: usize },
+    /// The file at the path could not be read.
+    Read(String, io::Error),
+    /// The file at the path could not be written.
+    Write(String, io::Error),
+    /// The terminal could not be used for interactive play.
+    Terminal(io::Error),
+}
+
+impl CliError {
+    fn exit_code(&self) -> i32 {
+        match self {
+            Self::NoSolution | Self::Batch { invalid: 0, .. } => 2,
+            Self::NotUnique | Self::Parse(_) | Self::Invalid(_) | Self::Batch { .. } => 3,
+            Self::Read(..) | Self::Write(..) | Self::Terminal(_) => 4,
+        }
+    }
+}
+
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::NoSolution => write!(f, "No solution found"),
+            Self::NotUnique => write!(f, "Sudoku has more than one solution"),
+            Self::Parse(e) => write!(f, "Could not parse sudoku: {}", e),
+            Self::Invalid(bad) => write!(f, "{} puzzles failed validation", bad),
+            Self::Batch { invalid, unsolved } => write!(
+                f,
+                "{} lines could not be parsed and {} had no solution",
+                invalid, unsolved
             ),
+            Self::Read(path, e) => write!(f, "Could not read file `{}`: {}", path, e),
+            Self::Write(path, e) => write!(f, "Could not write file `{}`: {}", path, e),
+            Self::Terminal(e) => write!(f, "Could not play in terminal: {}", e),
+        }
+    }
+}
+
+fn run(config: Config) -> Result<(), CliError> {
+    let src = match &config.file {
+        Some(file) => fs::read_to_string(file).map_err(|e| CliError::Read(file.clone(), e))?,
+        _ => match &config.uid {
+            Some(uid) => decode_uid(uid)?,
+            _ => String::from(DEFAULT_SUDOKU),
         },
+    };
+
+    let border = config.border.unwrap_or_else(BorderStyle::detect);
+    let theme = Theme::new().border(border.border());
+
+    if config.jsonl {
+        return print_jsonl(&src);
+    }
+    if config.batch {
+        return print_batch(&src, &theme);
+    }
+    if config.validate_only {
+        return validate(&src);
+    }
+    if config.dedup {
+        return dedup(&src);
+    }
+
+    let sudoku = Sudoku::from_str_validated(&src).map_err(CliError::Parse)?;
+
+    if config.interactive {
+        let mut game = SudokuGame::from_puzzle(sudoku).ok_or(CliError::NoSolution)?;
+        if play(&mut game, &theme).map_err(CliError::Terminal)? {
+            println!(
+                "{}\n{}        Solved!{}",
+                game.board().display_with(&theme),
+                SetForegroundColor(Color::Green),
+                Attribute::Reset
+            );
+        }
+        return Ok(());
+    }
+
+    if config.require_unique && sudoku.solve_unique() == UniqueResult::Multiple {
+        return Err(CliError::NotUnique);
+    }
+    if let Some(times) = config.repeat {
+        return benchmark(&sudoku, times);
     }
-    .unwrap_or_else(|e| {
-        eprintln!("Could not parse sudoku: {}", e);
-        process::exit(1);
-    });
 
     println!(
         "{}\n{}        Solving...{}",
-        sudoku,
+        sudoku.display_with(&theme),
         SetForegroundColor(Color::Red),
         Attribute::Reset
     );
-    if let Some(sudoku) = sudoku.solution(config.step, config.quiet) {
+    let step = Duration::from_millis(config.step);
+    if let Some(sudoku) = sudoku.solution_with(step, config.quiet, &theme) {
         println!(
             "{}\n{}{}{}          Done!{}{}",
-            sudoku,
+            sudoku.display_with(&theme),
             cursor::MoveUp(14),
             Clear(CurrentLine),
             SetForegroundColor(Color::Green),
             Attribute::Reset,
             cursor::MoveDown(13)
         );
+
+        if let Some(output) = &config.output {
+            fs::write(output, config.format.write(&sudoku))
+                .map_err(|e| CliError::Write(output.clone(), e))?;
+        }
+        Ok(())
     } else {
+        // Clear the solving status and move below the grid, leaving `main` to report the failure on stderr.
         println!(
-            "{}{}{}    No solution found{}{}{}",
+            "{}{}{}{}",
             cursor::MoveUp(1),
             Clear(CurrentLine),
-            SetForegroundColor(Color::Red),
-            Attribute::Reset,
             cursor::MoveDown(13),
             cursor::Show
         );
+        Err(CliError::NoSolution)
+    }
+}
+
+fn main() {
+    // Usage errors count as invalid input, so that exit code 2 always means no solution.
+    let config = Config::try_parse().unwrap_or_else(|e| {
+        if !e.use_stderr() {
+            e.exit();
+        }
+        let _ = e.print();
+        process::exit(3);
+    });
+
+    if let Err(e) = run(config) {
+        eprintln!("{}", e);
+        process::exit(e.exit_code());
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::str::FromStr;
+
+    const SOLVED: &str =
+        "157832496396745218284196753415378962763429185928561374831257649672984531549613827";
+
+    #[test]
+    fn decode_uid() {
+        assert_eq!(super::decode_uid(SOLVED).unwrap(), SOLVED);
+        assert_eq!(
+            super::decode_uid(&format!("line:{}", SOLVED)).unwrap(),
+            SOLVED
+        );
+        let b64 = Sudoku::from_str(SOLVED).unwrap().to_base64();
+        assert_eq!(super::decode_uid(&format!("b64:{}", b64)).unwrap(), SOLVED);
+        assert!(matches!(
+            super::decode_uid("b64:!"),
+            Err(CliError::Parse(ParseError::InvalidEncoding))
+        ));
+
+        // A blank written as `:` is not mistaken for the end of a scheme.
+        let blanked = SOLVED.replacen('8', ":", 1);
+        assert_eq!(super::decode_uid(&blanked).unwrap(), blanked);
+        assert_eq!(super::decode_uid(":2345").unwrap(), ":2345");
+    }
+
+    #[test]
+    fn border_for_locale() {
+        for locale in ["en_GB.UTF-8", "de_DE.utf8", "C.UTF-8"] {
+            assert!(
+                matches!(BorderStyle::for_locale(locale), BorderStyle::Unicode),
+                "{}",
+                locale
+            );
+        }
+        for locale in [
+            "",
+            "C",
+            "POSIX",
+            "en_US.ISO-8859-1",
+            "zh_CN.UTF-8",
+            "ja_JP.utf8",
+            "ko_KR.UTF-8",
+        ] {
+            assert!(
+                matches!(BorderStyle::for_locale(locale), BorderStyle::Ascii),
+                "{}",
+                locale
+            );
+        }
     }
 }
@@ -0,0 +1,135 @@
+//! A compact binary format for storing many grids in one file.
+//!
+//! A pack starts with a nine byte header:
+//!
+//! | Bytes | Contents                                      |
+//! |-------|-----------------------------------------------|
+//! | 0..4  | The magic bytes `NDKP`                        |
+//! | 4     | The format version, currently `1`             |
+//! | 5..9  | The number of grids, as a little-endian `u32` |
+//!
+//! Each grid then follows in 41 bytes, holding its cells in reading order packed two to a byte. Each cell takes four bits with the first cell of a byte in the high bits, `0` marking a blank and `1` to `9` its number. The low bits of the last byte are unused and must be zero. Only the numbers are stored, so variant constraints, the kind of each number and the blank character are lost.
+
+use crate::{ParseError, Sudoku};
+use std::io::{self, Read, Write};
+
+/// The bytes which start every pack.
+const MAGIC: &[u8; 4] = b"NDKP";
+/// The version of the pack layout written by [`Sudoku::write_pack`].
+const VERSION: u8 = 1;
+
+fn invalid(e: ParseError) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, e)
+}
+
+impl Sudoku {
+    /// Writes `grids` to `w` as a pack, four bits a cell after a small header. See the [module documentation](self) of the layout.
+    ///
+    /// Returns an error of kind [`io::ErrorKind::InvalidInput`] if there are more than [`u32::MAX`] grids, or any error from writing to `w`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use newdoku::Sudoku;
+    /// use std::str::FromStr;
+    ///
+    /// let grids = [
+    ///     Sudoku::from_str(&format!("1{}", ".".repeat(80))).unwrap(),
+    ///     Sudoku::from_str(&".".repeat(81)).unwrap(),
+    /// ];
+    /// let mut pack = Vec::new();
+    /// Sudoku::write_pack(&grids, &mut pack).unwrap();
+    /// assert_eq!(pack.len(), 9 + 2 * 41);
+    /// assert_eq!(Sudoku::read_pack(pack.as_slice()).unwrap(), grids);
+    /// ```
+    pub fn write_pack<W: Write>(grids: &[Sudoku], mut w: W) -> io::Result<()> {
+        let count = u32::try_from(grids.len()).map_err(|_| {
+            io::Error::new(io::ErrorKind::InvalidInput, "too many grids for a pack")
+        })?;
+        w.write_all(MAGIC)?;
+        w.write_all(&[VERSION])?;
+        w.write_all(&count.to_le_bytes())?;
+        for grid in grids {
+            w.write_all(&grid.to_nibbles())?;
+        }
+        w.flush()
+    }
+
+    /// Returns the grids of a pack read from `r`, with every number read as a given.
+    ///
+    /// Returns an error of kind [`io::ErrorKind::InvalidData`] wrapping a [`ParseError`] if the header is not that of a pack, a grid is malformed, or bytes follow the last grid, and of kind [`io::ErrorKind::UnexpectedEof`] if the pack ends early.
+    pub fn read_pack<R: Read>(mut r: R) -> io::Result<Vec<Sudoku>> {
+        let mut header = [0; 9];
+        r.read_exact(&mut header)?;
+        if &header[..4] != MAGIC || header[4] != VERSION {
+            return Err(invalid(ParseError::InvalidEncoding));
+        }
+        let count = u32::from_le_bytes([header[5], header[6], header[7], header[8]]) as usize;
+        // The count is untrusted, so grow as grids are read rather than reserving it all.
+        let mut grids = Vec::with_capacity(count.min(1024));
+        let mut bytes = [0; 41];
+        for _ in 0..count {
+            r.read_exact(&mut bytes)?;
+            grids.push(Self::from_nibbles(&bytes).map_err(invalid)?);
+        }
+        if r.read(&mut [0])? != 0 {
+            return Err(invalid(ParseError::InvalidEncoding));
+        }
+        Ok(grids)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use core::str::FromStr;
+
+    const SRC: &str =
+        "..3.2.6..9..3.5..1..18.64....81.29..7.......8..67.82....26.95..8..2.3..9..5.1.3..";
+
+    fn pack(grids: &[Sudoku]) -> Vec<u8> {
+        let mut pack = Vec::new();
+        Sudoku::write_pack(grids, &mut pack).unwrap();
+        pack
+    }
+
+    #[test]
+    fn round_trip() {
+        let s = Sudoku::from_str(SRC).unwrap();
+        let grids = [
+            s.clone(),
+            s.solve_nth(0).unwrap(),
+            Sudoku::from_str(&".".repeat(81)).unwrap(),
+        ];
+        let bytes = pack(&grids);
+        assert_eq!(&bytes[..9], b"NDKP\x01\x03\0\0\0");
+        assert_eq!(&bytes[9..50], &s.to_nibbles());
+        assert_eq!(Sudoku::read_pack(bytes.as_slice()).unwrap(), grids);
+        assert_eq!(Sudoku::read_pack(pack(&[]).as_slice()).unwrap(), []);
+    }
+
+    #[test]
+    fn invalid() {
+        let bytes = pack(&[Sudoku::from_str(SRC).unwrap()]);
+        let kind = |bytes: &[u8]| Sudoku::read_pack(bytes).unwrap_err().kind();
+
+        let mut magic = bytes.clone();
+        magic[0] = b'X';
+        assert_eq!(kind(&magic), io::ErrorKind::InvalidData);
+        let mut version = bytes.clone();
+        version[4] = 2;
+        assert_eq!(kind(&version), io::ErrorKind::InvalidData);
+        let mut value = bytes.clone();
+        value[9] = 0xa0;
+        assert_eq!(kind(&value), io::ErrorKind::InvalidData);
+        let mut padding = bytes.clone();
+        padding[49] |= 1;
+        assert_eq!(kind(&padding), io::ErrorKind::InvalidData);
+        let mut trailing = bytes.clone();
+        trailing.push(0);
+        assert_eq!(kind(&trailing), io::ErrorKind::InvalidData);
+
+        assert_eq!(kind(&bytes[..30]), io::ErrorKind::UnexpectedEof);
+        assert_eq!(kind(&bytes[..5]), io::ErrorKind::UnexpectedEof);
+    }
+}
@@ -0,0 +1,998 @@
+use crate::{
+    Sudoku,
+    SudokuNum::{Edited, Original},
+    TooManySolutions, Unit,
+};
+use alloc::{boxed::Box, vec, vec::Vec};
+
+/// Counters collected while searching.
+#[derive(Debug, Default)]
+pub(crate) struct Stats {
+    /// Number of branches taken on cells with more than one candidate.
+    pub guesses: usize,
+    /// Number of search nodes visited.
+    pub nodes: u64,
+    /// Number of nodes after which the search gives up, or [`None`] for no limit.
+    pub max_nodes: Option<u64>,
+}
+
+impl Stats {
+    /// Returns true if the node budget has been spent.
+    fn exhausted(&self) -> bool {
+        self.max_nodes.is_some_and(|max| self.nodes >= max)
+    }
+}
+
+/// Returns a solution of `sudoku`, or [`None`] if the givens are invalid or there is no solution.
+pub(crate) fn solve(sudoku: &Sudoku, stats: &mut Stats) -> Option<Sudoku> {
+    solve_ordered(sudoku, stats, &mut |_| {})
+}
+
+/// Like [`solve`], but lets `order` rearrange the candidates of each cell before they are tried.
+pub(crate) fn solve_ordered(
+    sudoku: &Sudoku,
+    stats: &mut Stats,
+    order: &mut dyn FnMut(&mut [u8]),
+) -> Option<Sudoku> {
+    if !sudoku.is_valid() {
+        return None;
+    }
+    search(sudoku.clone(), Strategy::Propagate, &[], stats, order)
+}
+
+/// Fills every empty cell that has a single candidate until none remain. Returns false if an empty cell has no candidates.
+fn propagate(sudoku: &mut Sudoku) -> bool {
+    loop {
+        let mut changed = false;
+        for idx in 0..81 {
+            if sudoku.xs[idx].is_some() {
+                continue;
+            }
+            let mask = sudoku.candidate_mask(idx);
+            if mask == 0 {
+                return false;
+            }
+            if mask.count_ones() == 1 {
+                sudoku.xs[idx] = Some(Edited(mask.trailing_zeros() as u8 + 1));
+                changed = true;
+            }
+        }
+        if !changed {
+            return true;
+        }
+    }
+}
+
+/// Returns the empty cell among `cells` with the fewest candidates along with its candidate mask.
+fn most_constrained(sudoku: &Sudoku, cells: impl Iterator<Item = usize>) -> Option<(usize, u16)> {
+    cells
+        .filter(|&idx| sudoku.xs[idx].is_none())
+        .map(|idx| (idx, sudoku.candidate_mask(idx)))
+        .min_by_key(|&(_, mask)| mask.count_ones())
+}
+
+/// Searches depth-first for a solution of `sudoku`, branching on the cells chosen by `strategy` once every unit of `priority` is full.
+fn search(
+    mut sudoku: Sudoku,
+    strategy: Strategy,
+    priority: &[Unit],
+    stats: &mut Stats,
+    order: &mut dyn FnMut(&mut [u8]),
+) -> Option<Sudoku> {
+    if stats.exhausted() {
+        return None;
+    }
+    stats.nodes += 1;
+    if strategy == Strategy::Propagate && !propagate(&mut sudoku) {
+        return None;
+    }
+    let (idx, mask) = match strategy.pick(&sudoku, priority) {
+        Some(cell) => cell,
+        None => return Some(sudoku),
+    };
+    if mask.count_ones() > 1 {
+        stats.guesses += 1;
+    }
+
+    let mut nums: Vec<u8> = (1..10).filter(|num| mask & 1 << (num - 1) != 0).collect();
+    order(&mut nums);
+    for num in nums {
+        let mut next = sudoku.clone();
+        next.xs[idx] = Some(Edited(num));
+        if let Some(solution) = search(next, strategy, priority, stats, order) {
+            return Some(solution);
+        }
+        if stats.exhausted() {
+            break;
+        }
+    }
+    None
+}
+
+/// A search algorithm for [`Sudoku::solve_with`]. Every strategy searches depth-first, so they all find the same solution of a puzzle with only one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Strategy {
+    /// Fills empty cells in reading order, trying each number allowed by the filled peers from 1 to 9. The animated [`Sudoku::solution`] searches in the same order.
+    BruteForce,
+    /// Fills the empty cell with the fewest candidates first (minimum remaining values).
+    Mrv,
+    /// Fills every naked single before each guess, then branches like [`Strategy::Mrv`]. This is the strategy used by [`Sudoku::solutions`], [`Sudoku::solve_bounded`] and the other solving methods which do not animate.
+    Propagate,
+}
+
+impl Strategy {
+    /// Returns the empty cell of `sudoku` to branch on next along with its candidate mask, or [`None`] if `sudoku` is full.
+    ///
+    /// While any unit of `priority` has an empty cell, the most constrained empty cell of the first such unit is chosen.
+    fn pick(self, sudoku: &Sudoku, priority: &[Unit]) -> Option<(usize, u16)> {
+        let first = priority
+            .iter()
+            .find_map(|&unit| most_constrained(sudoku, sudoku.unit_cells(unit).iter().copied()));
+        if first.is_some() {
+            return first;
+        }
+        match self {
+            Self::BruteForce => (0..81)
+                .find(|&idx| sudoku.xs[idx].is_none())
+                .map(|idx| (idx, sudoku.candidate_mask(idx))),
+            Self::Mrv | Self::Propagate => most_constrained(sudoku, 0..81),
+        }
+    }
+}
+
+/// The result of [`Sudoku::solve_bounded`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum SolveOutcome {
+    /// A solution was found within the budget, which is held.
+    Solved(Box<Sudoku>),
+    /// The whole search space was explored within the budget without finding a solution.
+    NoSolution,
+    /// The budget was spent before the search finished.
+    Exhausted,
+}
+
+/// An iterator over the solutions of a [`Sudoku`] in depth-first order. Returned by [`Sudoku::solutions`].
+#[derive(Debug, Clone)]
+pub struct Solutions {
+    /// Grids still to be explored, with the next one to explore last.
+    stack: Vec<Sudoku>,
+}
+
+impl Iterator for Solutions {
+    type Item = Sudoku;
+
+    fn next(&mut self) -> Option<Sudoku> {
+        while let Some(mut sudoku) = self.stack.pop() {
+            if !propagate(&mut sudoku) {
+                continue;
+            }
+            let (idx, mask) = match Strategy::Propagate.pick(&sudoku, &[]) {
+                Some(cell) => cell,
+                None => return Some(sudoku),
+            };
+            for num in (1..10).rev().filter(|num| mask & 1 << (num - 1) != 0) {
+                let mut next = sudoku.clone();
+                next.xs[idx] = Some(Edited(num));
+                self.stack.push(next);
+            }
+        }
+        None
+    }
+}
+
+/// Whether a [`Sudoku`] has exactly one solution, as returned by [`Sudoku::solve_unique`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum UniqueResult {
+    /// The puzzle has exactly one solution, which is held.
+    Unique(Box<Sudoku>),
+    /// The puzzle has more than one solution.
+    Multiple,
+    /// The puzzle has no solution.
+    None,
+}
+
+impl Sudoku {
+    /// Returns an iterator over every solution of `self` in depth-first order, trying smaller candidates first. Solutions are found lazily, so taking only the first few is cheap.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use newdoku::Sudoku;
+    /// use std::str::FromStr;
+    ///
+    /// let s = Sudoku::from_str(
+    ///     "xxxxxxx9xx9x7xx21xxx4x9xxxxx1xxx8xxx7xx42xxx5xx8xxxx748x1xxxx4xxxxxxxxxxxx9613xxx",
+    /// ).unwrap();
+    /// assert_eq!(s.solutions().count(), 1);
+    /// ```
+    pub fn solutions(&self) -> Solutions {
+        Solutions {
+            stack: if self.is_valid() {
+                vec![self.clone()]
+            } else {
+                Vec::new()
+            },
+        }
+    }
+
+    /// Returns the `n`th solution (0-indexed) of `self` in the order of [`Sudoku::solutions`], or [`None`] if there are at most `n` solutions.
+    ///
+    /// A uniquely solvable puzzle only yields a result for `n == 0`.
+    pub fn solve_nth(&self, n: usize) -> Option<Self> {
+        self.solutions().nth(n)
+    }
+
+    /// Returns every solution of `self` in the depth-first order of [`Sudoku::solutions`], or [`TooManySolutions`] if there are more than `cap`.
+    ///
+    /// Searching stops as soon as the cap is exceeded, so under-constrained grids cannot exhaust memory.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use newdoku::{Sudoku, TooManySolutions};
+    /// use std::str::FromStr;
+    ///
+    /// let s = Sudoku::from_str(&".".repeat(81)).unwrap();
+    /// assert_eq!(s.all_solutions(100), Err(TooManySolutions));
+    /// ```
+    pub fn all_solutions(&self, cap: usize) -> Result<Vec<Self>, TooManySolutions> {
+        let solutions: Vec<Self> = self.solutions().take(cap.saturating_add(1)).collect();
+        if solutions.len() > cap {
+            return Err(TooManySolutions);
+        }
+        Ok(solutions)
+    }
+
+    /// Returns the number of solutions of `self`, stopping once `limit` have been found.
+    pub fn count_solutions(&self, limit: usize) -> usize {
+        self.solutions().take(limit).count()
+    }
+
+    /// Returns the cells which hold the same number in every one of the first `cap` solutions of `self` in the order of [`Sudoku::solutions`], with the other cells empty, and how many solutions were compared. Returns [`None`] if `self` has no solution.
+    ///
+    /// Givens are always forced. If `self` has more than `cap` solutions, some of the cells returned may differ in a solution which was not compared. At least one solution is always compared, even if `cap` is 0.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use newdoku::Sudoku;
+    /// use std::str::FromStr;
+    ///
+    /// // Swapping the 4s and 7s of R1C3, R1C7, R3C3 and R3C7 gives a second solution.
+    /// let s = Sudoku::from_str(
+    ///     "15.832.9639674521828.196.53415378962763429185928561374831257649672984531549613827",
+    /// ).unwrap();
+    /// let (forced, count) = s.solve_all_then_intersect(100).unwrap();
+    /// assert_eq!(count, 2);
+    /// assert_eq!(forced.to_line(), s.to_line());
+    /// ```
+    pub fn solve_all_then_intersect(&self, cap: usize) -> Option<(Self, usize)> {
+        let mut solutions = self.solutions().take(cap.max(1));
+        let mut forced = solutions.next()?;
+        let mut count = 1;
+        for solution in solutions {
+            for (x, other) in forced.xs.iter_mut().zip(solution.xs) {
+                if *x != other {
+                    *x = None;
+                }
+            }
+            count += 1;
+        }
+        Some((forced, count))
+    }
+
+    /// Returns the number which the cell at `(x, y)` holds in every solution of `self`, or [`None`] if its solutions disagree or `self` has no solution. A filled cell gives its number, as long as `self` has a solution.
+    ///
+    /// Unlike naked and hidden singles this finds every cell which can be placed with certainty, however hard the deduction. It costs up to one search per candidate of the cell, so it is much slower than [`Sudoku::candidates`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `x` or `y` is not within range 0..9.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use newdoku::Sudoku;
+    /// use std::str::FromStr;
+    ///
+    /// let s = Sudoku::from_str(
+    ///     "15.832.9639674521828.196.53415378962763429185928561374831257649672984531549613827",
+    /// ).unwrap();
+    /// assert_eq!(s.is_determined(2, 0), None);
+    /// assert_eq!(s.is_determined(0, 0), Some(1));
+    /// assert_eq!(s.try_insert((2, 0), 7).unwrap().is_determined(6, 0), Some(4));
+    /// ```
+    pub fn is_determined(&self, x: usize, y: usize) -> Option<u8> {
+        assert!(x < 9 && y < 9, "each coordinate must be within range 0..9");
+        let idx = y * 9 + x;
+        if let Some(num) = self.xs[idx] {
+            return self.solutions().next().map(|_| num.value());
+        }
+
+        let mut found = None;
+        for num in self.candidates(x, y) {
+            let mut sudoku = self.clone();
+            sudoku.xs[idx] = Some(Edited(num));
+            if sudoku.solutions().next().is_some() {
+                if found.is_some() {
+                    return None;
+                }
+                found = Some(num);
+            }
+        }
+        found
+    }
+
+    /// Returns true if the givens of `self` have a unique solution which removing any one of them would lose. Edited cells are treated as empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use newdoku::Sudoku;
+    /// use std::str::FromStr;
+    ///
+    /// let s = Sudoku::from_str(
+    ///     "xxxxxxx9xx9x7xx21xxx4x9xxxxx1xxx8xxx7xx42xxx5xx8xxxx748x1xxxx4xxxxxxxxxxxx9613xxx",
+    /// ).unwrap();
+    /// let solved = Sudoku::from_str(
+    ///     "157832496396745218284196753415378962763429185928561374831257649672984531549613827",
+    /// ).unwrap();
+    /// assert!(!solved.is_minimal());
+    /// assert!(!Sudoku::from_str(&".".repeat(81)).unwrap().is_minimal());
+    /// ```
+    pub fn is_minimal(&self) -> bool {
+        let mut puzzle = self.givens();
+        if puzzle.count_solutions(2) != 1 {
+            return false;
+        }
+        (0..81).all(|idx| match puzzle.xs[idx].take() {
+            Some(given) => {
+                let redundant = puzzle.count_solutions(2) == 1;
+                puzzle.xs[idx] = Some(given);
+                !redundant
+            }
+            None => true,
+        })
+    }
+
+    /// Returns a solution of `self` found with `strategy`, or [`None`] if the givens are invalid or there is no solution.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use newdoku::{Strategy, Sudoku};
+    /// use std::str::FromStr;
+    ///
+    /// let s = Sudoku::from_str(
+    ///     "xxxxxxx9xx9x7xx21xxx4x9xxxxx1xxx8xxx7xx42xxx5xx8xxxx748x1xxxx4xxxxxxxxxxxx9613xxx",
+    /// ).unwrap();
+    /// let solution = s.solve_with(Strategy::Propagate);
+    /// assert!(solution.is_some());
+    /// assert_eq!(s.solve_with(Strategy::Mrv), solution);
+    /// ```
+    pub fn solve_with(&self, strategy: Strategy) -> Option<Self> {
+        if !self.is_valid() {
+            return None;
+        }
+        search(
+            self.clone(),
+            strategy,
+            &[],
+            &mut Stats::default(),
+            &mut |_| {},
+        )
+    }
+
+    /// Returns a solution of `self` found by filling the empty cells of each of `units` in turn before the rest, or [`None`] if the givens are invalid or there is no solution.
+    ///
+    /// Within each unit, and once every unit is full, the cell with the fewest candidates is filled first as with [`Strategy::Mrv`]. Prioritising the units of a variant constraint, such as the diagonals added by [`Sudoku::with_diagonals`], can make it prune earlier. The search is still depth-first, so a puzzle with one solution gives the same solution as [`Sudoku::solve_with`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if a unit does not exist, such as [`Unit::Row`] with an index above 8 or a [`Unit::Region`] which was never added.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use newdoku::{Strategy, Sudoku, Unit};
+    /// use std::str::FromStr;
+    ///
+    /// let s = Sudoku::from_str(
+    ///     "xxxxxxx9xx9x7xx21xxx4x9xxxxx1xxx8xxx7xx42xxx5xx8xxxx748x1xxxx4xxxxxxxxxxxx9613xxx",
+    /// ).unwrap();
+    /// let solution = s.solve_prioritizing(&[Unit::Block(4), Unit::Row(0)]);
+    /// assert_eq!(solution, s.solve_with(Strategy::Mrv));
+    /// ```
+    pub fn solve_prioritizing(&self, units: &[Unit]) -> Option<Self> {
+        if !self.is_valid() {
+            return None;
+        }
+        search(
+            self.clone(),
+            Strategy::Mrv,
+            units,
+            &mut Stats::default(),
+            &mut |_| {},
+        )
+    }
+
+    /// Solves `self` while visiting at most `max_nodes` nodes of the search tree, giving [`SolveOutcome::Exhausted`] once the budget is spent.
+    ///
+    /// Unlike a time limit, the budget gives the same outcome on every machine, so it suits judges and shared servers which must bound the work done on crafted inputs. Filling naked singles happens within a node, so a puzzle solvable without guessing needs only one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use newdoku::{SolveOutcome, Sudoku};
+    /// use std::str::FromStr;
+    ///
+    /// let s = Sudoku::from_str(&".".repeat(81)).unwrap();
+    /// assert_eq!(s.solve_bounded(0), SolveOutcome::Exhausted);
+    /// assert!(matches!(s.solve_bounded(1000), SolveOutcome::Solved(_)));
+    /// ```
+    pub fn solve_bounded(&self, max_nodes: u64) -> SolveOutcome {
+        let mut stats = Stats {
+            max_nodes: Some(max_nodes),
+            ..Default::default()
+        };
+        match solve(self, &mut stats) {
+            Some(solution) => SolveOutcome::Solved(Box::new(solution)),
+            None if stats.exhausted() => SolveOutcome::Exhausted,
+            None => SolveOutcome::NoSolution,
+        }
+    }
+
+    /// Returns the solution of `self` only if it is the sole solution, so that a puzzle with several solutions is rejected rather than completed arbitrarily.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use newdoku::{Sudoku, UniqueResult};
+    /// use std::str::FromStr;
+    ///
+    /// let s = Sudoku::from_str(&".".repeat(81)).unwrap();
+    /// assert_eq!(s.solve_unique(), UniqueResult::Multiple);
+    /// ```
+    pub fn solve_unique(&self) -> UniqueResult {
+        let mut solutions = self.solutions();
+        match (solutions.next(), solutions.next()) {
+            (Some(solution), None) => UniqueResult::Unique(Box::new(solution)),
+            (Some(_), Some(_)) => UniqueResult::Multiple,
+            _ => UniqueResult::None,
+        }
+    }
+
+    /// Returns a solution of `self` in which none of the cells `(x, y, num)` of `exclusions` holds `num`, or [`None`] if there is no such solution.
+    ///
+    /// This answers questions like "is there another solution where the top left cell is not 5?". Exclusions outside the grid or with a digit outside 1..=9 have no effect.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use newdoku::Sudoku;
+    /// use std::str::FromStr;
+    ///
+    /// let s = Sudoku::from_str(
+    ///     "xxxxxxx9xx9x7xx21xxx4x9xxxxx1xxx8xxx7xx42xxx5xx8xxxx748x1xxxx4xxxxxxxxxxxx9613xxx",
+    /// ).unwrap();
+    /// let solution = s.solve_excluding(&[]).unwrap();
+    /// assert_eq!(solution.to_line().as_bytes()[0], b'1');
+    /// assert_eq!(s.solve_excluding(&[(0, 0, 1)]), None);
+    /// ```
+    pub fn solve_excluding(&self, exclusions: &[(usize, usize, u8)]) -> Option<Self> {
+        let mut sudoku = self.clone();
+        for &(x, y, num) in exclusions {
+            if x > 8 || y > 8 || num == 0 || num > 9 {
+                continue;
+            }
+            let idx = y * 9 + x;
+            match sudoku.xs[idx] {
+                Some(value) if value.value() == num => return None,
+                Some(_) => {}
+                None => sudoku.variants.excluded.push((idx, num)),
+            }
+        }
+
+        let mut solution = solve(&sudoku, &mut Stats::default())?;
+        solution.variants.excluded.clear();
+        Some(solution)
+    }
+
+    /// Returns clues `(x, y, num)` taken from one solution of `self` which make it uniquely solvable when added as givens. Returns an empty [`Vec`] if `self` already has a single solution or has none.
+    ///
+    /// Clues are added greedily: while a second solution exists, a cell where it differs from the first is given the first solution's digit, ruling the second out. Afterwards any clue not needed by the others is dropped again, so the result is small but not necessarily the smallest possible.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use newdoku::Sudoku;
+    /// use std::str::FromStr;
+    ///
+    /// let mut s = Sudoku::from_str(
+    ///     "xxxxxxx9xx9x7xx21xxx4x9xxxxx1xxx8xxx7xx42xxx5xx8xxxx748x1xxxx4xxxxxxxxxxxx9xxxxxx",
+    /// ).unwrap();
+    /// assert!(s.count_solutions(2) > 1);
+    /// for (x, y, num) in s.minimal_additions() {
+    ///     s.with_given(x, y, num).unwrap();
+    /// }
+    /// assert_eq!(s.count_solutions(2), 1);
+    /// ```
+    pub fn minimal_additions(&self) -> Vec<(usize, usize, u8)> {
+        let mut solutions = self.solutions();
+        let first = match solutions.next() {
+            Some(first) => first,
+            None => return Vec::new(),
+        };
+        let value = |idx: usize| first.xs[idx].map_or(0, |num| num.value());
+
+        let mut puzzle = self.clone();
+        let mut added = Vec::new();
+        while puzzle.count_solutions(2) > 1 {
+            // One of the first two solutions differs from `first`.
+            let idx = puzzle
+                .solutions()
+                .take(2)
+                .find_map(|other| {
+                    (0..81).find(|&idx| other.xs[idx].map(|num| num.value()) != Some(value(idx)))
+                })
+                .expect("distinct solutions differ in some cell");
+            puzzle.xs[idx] = Some(Original(value(idx)));
+            added.push(idx);
+        }
+
+        // Clues added early may be implied by later ones.
+        for i in (0..added.len()).rev() {
+            let idx = added[i];
+            puzzle.xs[idx] = None;
+            if puzzle.count_solutions(2) > 1 {
+                puzzle.xs[idx] = Some(Original(value(idx)));
+            } else {
+                added.remove(i);
+            }
+        }
+
+        added.sort_unstable();
+        added
+            .into_iter()
+            .map(|idx| (idx % 9, idx / 9, value(idx)))
+            .collect()
+    }
+}
+
+/// Maximum number of alternative completions examined per group of blanked digits in [`Sudoku::unavoidable_sets`].
+const UNAVOIDABLE_CAP: usize = 64;
+
+impl Sudoku {
+    /// Returns unavoidable sets of a complete grid with at most `max_size` cells: sets of cells whose values could be rearranged to give another valid grid, so any puzzle with this solution must have a given in every set.
+    ///
+    /// Sets are found by blanking every pair and triple of digits and comparing the other completions against `self`, so the result is not guaranteed to be exhaustive. Only sets which do not contain another found set are returned, ordered by size. Returns an empty list if `self` is not a complete, valid grid.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use newdoku::Sudoku;
+    /// use std::str::FromStr;
+    ///
+    /// let s = Sudoku::from_str(
+    ///     "157832496396745218284196753415378962763429185928561374831257649672984531549613827",
+    /// ).unwrap();
+    /// assert!(s.unavoidable_sets(4).contains(&vec![(2, 0), (6, 0), (2, 2), (6, 2)]));
+    /// ```
+    pub fn unavoidable_sets(&self, max_size: usize) -> Vec<Vec<(usize, usize)>> {
+        if !self.is_full() || !self.is_valid() {
+            return Vec::new();
+        }
+
+        // Each set is stored as a bitset with bit `idx` set for each cell.
+        let mut sets: Vec<u128> = Vec::new();
+        for digits in (0u16..0x200).filter(|d| (2..=3).contains(&d.count_ones())) {
+            let mut blanked = self.clone();
+            for x in blanked.xs.iter_mut() {
+                if x.is_some_and(|num| digits & 1 << (num.value() - 1) != 0) {
+                    *x = None;
+                }
+            }
+
+            for solution in blanked.solutions().take(UNAVOIDABLE_CAP) {
+                let set = (0..81)
+                    .filter(|&i| solution.xs[i] != self.xs[i])
+                    .fold(0u128, |set, i| set | 1 << i);
+                if set != 0 && set.count_ones() as usize <= max_size {
+                    sets.push(set);
+                }
+            }
+        }
+
+        sets.sort_unstable_by_key(|&set| (set.count_ones(), set));
+        sets.dedup();
+        let mut minimal: Vec<u128> = Vec::new();
+        for set in sets {
+            if minimal.iter().all(|&m| m & set != m) {
+                minimal.push(set);
+            }
+        }
+
+        minimal
+            .into_iter()
+            .map(|set| {
+                (0..81)
+                    .filter(|i| set & 1 << i != 0)
+                    .map(|i| (i % 9, i / 9))
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+/// How the search ended at a [`SearchNode`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SearchOutcome {
+    /// The placement is part of the solution that was found.
+    Solved,
+    /// Every continuation of the placement was tried without finding a solution.
+    DeadEnd,
+    /// The node limit was reached before the placement was fully explored.
+    Truncated,
+}
+
+/// A placement attempted by the solver, along with the placements attempted after it. Returned by [`Sudoku::solve_tree`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchNode {
+    /// The number placed at `(x, y)`, or [`None`] for the root.
+    pub placement: Option<((usize, usize), u8)>,
+    /// How the search ended at this placement.
+    pub outcome: SearchOutcome,
+    /// The placements attempted after this one, in the order they were tried.
+    pub children: Vec<SearchNode>,
+}
+
+impl SearchNode {
+    /// Returns the number of nodes in the tree rooted at `self`, including `self`.
+    pub fn node_count(&self) -> usize {
+        1 + self.children.iter().map(Self::node_count).sum::<usize>()
+    }
+}
+
+/// Explores `sudoku` in the same order as [`Sudoku::solution`], spending one unit of `budget` per placement.
+fn tree(
+    sudoku: &Sudoku,
+    placement: Option<((usize, usize), u8)>,
+    budget: &mut usize,
+) -> SearchNode {
+    let mut node = SearchNode {
+        placement,
+        outcome: SearchOutcome::DeadEnd,
+        children: Vec::new(),
+    };
+    let idx = match sudoku.xs.iter().position(Option::is_none) {
+        Some(idx) => idx,
+        None => {
+            node.outcome = SearchOutcome::Solved;
+            return node;
+        }
+    };
+
+    let loc = (idx % 9, idx / 9);
+    for num in 1..10 {
+        if let Ok(next) = sudoku.try_insert(loc, num) {
+            if *budget == 0 {
+                node.outcome = SearchOutcome::Truncated;
+                return node;
+            }
+            *budget -= 1;
+
+            let child = tree(&next, Some((loc, num)), budget);
+            let outcome = child.outcome;
+            node.children.push(child);
+            if outcome != SearchOutcome::DeadEnd {
+                node.outcome = outcome;
+                return node;
+            }
+        }
+    }
+    node
+}
+
+impl Sudoku {
+    /// Returns the tree of placements the solver attempts while solving `self`, including dead ends, in the same order as [`Sudoku::solution`].
+    ///
+    /// The search stops once a solution is found. At most `max_nodes` nodes are recorded (always including the root); any node that could not be fully explored within the limit is marked [`SearchOutcome::Truncated`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use newdoku::{SearchOutcome, Sudoku};
+    /// use std::str::FromStr;
+    ///
+    /// let s = Sudoku::from_str(
+    ///     "xxxxxxx9xx9x7xx21xxx4x9xxxxx1xxx8xxx7xx42xxx5xx8xxxx748x1xxxx4xxxxxxxxxxxx9613xxx",
+    /// ).unwrap();
+    /// let tree = s.solve_tree(100);
+    /// assert_eq!(tree.node_count(), 100);
+    /// assert_eq!(tree.outcome, SearchOutcome::Truncated);
+    /// ```
+    pub fn solve_tree(&self, max_nodes: usize) -> SearchNode {
+        tree(self, None, &mut max_nodes.saturating_sub(1))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::str::FromStr;
+    const TEST_SUDOKU: &str =
+        "xxxxxxx9xx9x7xx21xxx4x9xxxxx1xxx8xxx7xx42xxx5xx8xxxx748x1xxxx4xxxxxxxxxxxx9613xxx";
+    const SOLVED_SUDOKU: &str =
+        "157832496396745218284196753415378962763429185928561374831257649672984531549613827";
+
+    #[test]
+    fn is_determined() {
+        let s = Sudoku::from_str(TEST_SUDOKU).unwrap();
+        let solved = Sudoku::from_str(SOLVED_SUDOKU).unwrap();
+        for i in (0..81).step_by(8) {
+            assert_eq!(
+                s.is_determined(i % 9, i / 9),
+                solved.xs[i].map(|num| num.value())
+            );
+        }
+        let empty = Sudoku::from_str(&".".repeat(81)).unwrap();
+        assert_eq!(empty.is_determined(4, 4), None);
+        let invalid = Sudoku::from_str(&format!("11{}", ".".repeat(79))).unwrap();
+        assert_eq!(invalid.is_determined(0, 0), None);
+        assert_eq!(invalid.is_determined(5, 5), None);
+    }
+
+    #[test]
+    fn solve_all_then_intersect() {
+        let solved = Sudoku::from_str(SOLVED_SUDOKU).unwrap();
+        assert_eq!(
+            solved.solve_all_then_intersect(10),
+            Some((solved.clone(), 1))
+        );
+
+        let s = Sudoku::from_str(TEST_SUDOKU).unwrap();
+        let mut open = s.clone();
+        open.xs[7] = None;
+        let (forced, count) = open.solve_all_then_intersect(1000).unwrap();
+        assert!(count > 1);
+        assert!((0..81).all(|i| open.xs[i].is_none() || forced.xs[i] == open.xs[i]));
+        let (first, one) = open.solve_all_then_intersect(0).unwrap();
+        assert_eq!((Some(first), one), (open.solutions().next(), 1));
+
+        let invalid = Sudoku::from_str(&format!("11{}", ".".repeat(79))).unwrap();
+        assert_eq!(invalid.solve_all_then_intersect(10), None);
+    }
+
+    #[test]
+    fn solve() {
+        let s = Sudoku::from_str(TEST_SUDOKU).unwrap();
+        assert_eq!(
+            super::solve(&s, &mut Stats::default()),
+            Some(Sudoku::from_str(SOLVED_SUDOKU).unwrap())
+        );
+    }
+
+    #[test]
+    fn solutions() {
+        // Removing a rectangle of 7s and 4s spanning two blocks gives two solutions.
+        let mut s = Sudoku::from_str(SOLVED_SUDOKU).unwrap();
+        for idx in [2, 6, 20, 24] {
+            s.xs[idx] = None;
+        }
+        let solutions: Vec<Sudoku> = s.solutions().collect();
+        assert_eq!(solutions.len(), 2);
+        assert!(solutions.iter().all(|s| s.is_full() && s.is_valid()));
+        assert_ne!(solutions[0], solutions[1]);
+        assert_eq!(s.solve_nth(1), Some(solutions[1].clone()));
+        assert_eq!(s.solve_nth(2), None);
+    }
+
+    #[test]
+    fn all_solutions() {
+        let mut s = Sudoku::from_str(SOLVED_SUDOKU).unwrap();
+        for idx in [2, 6, 20, 24] {
+            s.xs[idx] = None;
+        }
+        let solutions = s.all_solutions(2).unwrap();
+        assert_eq!(solutions, s.solutions().collect::<Vec<_>>());
+        assert_eq!(solutions.len(), 2);
+        assert_eq!(s.all_solutions(1), Err(TooManySolutions));
+
+        let invalid = Sudoku::from_str(&format!("11{}", ".".repeat(79))).unwrap();
+        assert_eq!(invalid.all_solutions(0), Ok(Vec::new()));
+    }
+
+    #[test]
+    fn solve_unique() {
+        let s = Sudoku::from_str(TEST_SUDOKU).unwrap();
+        assert_eq!(
+            s.solve_unique(),
+            UniqueResult::Unique(Box::new(Sudoku::from_str(SOLVED_SUDOKU).unwrap()))
+        );
+        assert_eq!(s.count_solutions(2), 1);
+
+        let mut s = Sudoku::from_str(SOLVED_SUDOKU).unwrap();
+        for idx in [2, 6, 20, 24] {
+            s.xs[idx] = None;
+        }
+        assert_eq!(s.solve_unique(), UniqueResult::Multiple);
+        assert_eq!(s.count_solutions(10), 2);
+        assert_eq!(s.count_solutions(1), 1);
+
+        let s = Sudoku::from_str(&format!("{}5", &TEST_SUDOKU[..80])).unwrap();
+        assert_eq!(s.solve_unique(), UniqueResult::None);
+        assert_eq!(s.count_solutions(2), 0);
+    }
+
+    #[test]
+    fn solve_excluding() {
+        let solved = Sudoku::from_str(SOLVED_SUDOKU).unwrap();
+        let mut s = solved.clone();
+        for idx in [2, 6, 20, 24] {
+            s.xs[idx] = None;
+        }
+        let first = s.solve_excluding(&[]).unwrap();
+        let second = s
+            .solve_excluding(&[(2, 0, first.to_line().as_bytes()[2] - b'0')])
+            .unwrap();
+        assert_ne!(first, second);
+        assert!(second.is_valid() && second.is_full());
+        assert!(second.variants.excluded.is_empty());
+        assert_eq!(s.solve_excluding(&[(0, 0, 1)]), None);
+        assert_eq!(s.solve_excluding(&[(9, 0, 1), (0, 0, 0)]), Some(first));
+    }
+
+    #[test]
+    fn solve_with() {
+        let s = Sudoku::from_str(TEST_SUDOKU).unwrap();
+        let solved = Sudoku::from_str(SOLVED_SUDOKU).unwrap();
+        for strategy in [Strategy::BruteForce, Strategy::Mrv, Strategy::Propagate] {
+            assert_eq!(
+                s.solve_with(strategy),
+                Some(solved.clone()),
+                "{:?}",
+                strategy
+            );
+        }
+        assert_eq!(
+            s.solve_with(Strategy::Propagate),
+            super::solve(&s, &mut Stats::default())
+        );
+
+        let unsolvable = Sudoku::from_str(&format!("{}5", &TEST_SUDOKU[..80])).unwrap();
+        assert_eq!(unsolvable.solve_with(Strategy::BruteForce), None);
+        let invalid = Sudoku::from_str(&format!("11{}", ".".repeat(79))).unwrap();
+        assert_eq!(invalid.solve_with(Strategy::Mrv), None);
+    }
+
+    #[test]
+    fn solve_prioritizing() {
+        let s = Sudoku::from_str(TEST_SUDOKU).unwrap();
+        let (idx, _) = Strategy::Mrv.pick(&s, &[Unit::Block(8)]).unwrap();
+        assert!(idx / 9 >= 6 && idx % 9 >= 6);
+        assert_ne!(
+            Strategy::Mrv.pick(&s, &[]),
+            Strategy::Mrv.pick(&s, &[Unit::Block(8)])
+        );
+
+        let solved = Sudoku::from_str(SOLVED_SUDOKU).unwrap();
+        assert_eq!(Strategy::Mrv.pick(&solved, &[Unit::Row(0)]), None);
+        assert_eq!(
+            s.solve_prioritizing(&[Unit::Block(8), Unit::Col(0)]),
+            Some(solved)
+        );
+
+        let diagonal = Sudoku::from_str(&".".repeat(81)).unwrap().with_diagonals();
+        let solution = diagonal.solve_prioritizing(&[Unit::Region(0)]).unwrap();
+        assert!(solution.is_full() && solution.is_valid());
+    }
+
+    #[test]
+    fn solve_bounded() {
+        let s = Sudoku::from_str(TEST_SUDOKU).unwrap();
+        let mut stats = Stats::default();
+        super::solve(&s, &mut stats).unwrap();
+        assert_eq!(
+            s.solve_bounded(stats.nodes),
+            SolveOutcome::Solved(Box::new(Sudoku::from_str(SOLVED_SUDOKU).unwrap()))
+        );
+        assert_eq!(s.solve_bounded(stats.nodes - 1), SolveOutcome::Exhausted);
+        assert_eq!(
+            Sudoku::from_str(SOLVED_SUDOKU).unwrap().solve_bounded(1),
+            SolveOutcome::Solved(Box::new(Sudoku::from_str(SOLVED_SUDOKU).unwrap()))
+        );
+
+        let unsolvable = Sudoku::from_str(&format!("{}5", &TEST_SUDOKU[..80])).unwrap();
+        assert_eq!(unsolvable.solve_bounded(u64::MAX), SolveOutcome::NoSolution);
+    }
+
+    #[test]
+    fn minimal_additions() {
+        let mut s = Sudoku::from_str(SOLVED_SUDOKU).unwrap();
+        for idx in [2, 6, 20, 24] {
+            s.xs[idx] = None;
+        }
+        let additions = s.minimal_additions();
+        assert_eq!(additions.len(), 1);
+        let (x, y, num) = additions[0];
+        s.with_given(x, y, num).unwrap();
+        assert_eq!(s.count_solutions(2), 1);
+
+        assert!(Sudoku::from_str(TEST_SUDOKU)
+            .unwrap()
+            .minimal_additions()
+            .is_empty());
+        let mut empty = Sudoku::from_str(&".".repeat(81)).unwrap();
+        for (x, y, num) in empty.minimal_additions() {
+            empty.with_given(x, y, num).unwrap();
+        }
+        assert_eq!(empty.count_solutions(2), 1);
+    }
+
+    #[test]
+    fn unavoidable_sets() {
+        let solved = Sudoku::from_str(SOLVED_SUDOKU).unwrap();
+        let sets = solved.unavoidable_sets(6);
+        assert!(!sets.is_empty());
+        for set in &sets {
+            assert!((4..=6).contains(&set.len()));
+            let mut s = solved.clone();
+            for &(x, y) in set {
+                s.xs[y * 9 + x] = None;
+            }
+            assert!(s.solutions().nth(1).is_some());
+        }
+        assert!(Sudoku::from_str(TEST_SUDOKU)
+            .unwrap()
+            .unavoidable_sets(6)
+            .is_empty());
+    }
+
+    #[test]
+    fn solve_tree() {
+        let s = Sudoku::from_str(&SOLVED_SUDOKU.replace('7', ".")).unwrap();
+        let tree = s.solve_tree(1000);
+        assert_eq!(tree.outcome, SearchOutcome::Solved);
+
+        let mut node = &tree;
+        let mut solved = s.clone();
+        while let Some(child) = node.children.last() {
+            assert_eq!(child.outcome, SearchOutcome::Solved);
+            let (loc, num) = child.placement.unwrap();
+            solved = solved.try_insert(loc, num).unwrap();
+            node = child;
+        }
+        assert_eq!(solved, Sudoku::from_str(SOLVED_SUDOKU).unwrap());
+    }
+
+    #[test]
+    fn solve_tree_dead_end() {
+        let s = Sudoku::from_str(
+            "xxxxxxx9xx9x7xx21xxx4x9xxxxx1xxx8xxx7xx42xxx5xx8xxxx748x1xxxx4xxxxxxxxxxxx9613xx5",
+        )
+        .unwrap();
+        let tree = s.solve_tree(usize::MAX);
+        assert_eq!(tree.outcome, SearchOutcome::DeadEnd);
+        assert!(tree
+            .children
+            .iter()
+            .all(|c| c.outcome == SearchOutcome::DeadEnd));
+    }
+
+    #[test]
+    fn guess_count() {
+        assert!(Sudoku::from_str(TEST_SUDOKU)
+            .unwrap()
+            .guess_count()
+            .is_some());
+        assert!(Sudoku::from_str(&".".repeat(81)).unwrap().guess_count() > Some(0));
+        assert_eq!(
+            Sudoku::from_str(
+                "xxxxxxx9xx9x7xx21xxx4x9xxxxx1xxx8xxx7xx42xxx5xx8xxxx748x1xxxx4xxxxxxxxxxxx9613xx5",
+            )
+            .unwrap()
+            .guess_count(),
+            None
+        );
+    }
+}
@@ -0,0 +1,143 @@
+//! Characters standing for the numbers of a grid, for reading and drawing puzzles which use letters or other symbols in place of digits.
+
+use crate::{ParseError, Sudoku, SudokuNum};
+use alloc::vec::Vec;
+
+/// The characters which stand for the numbers 1 to 9 when parsing with [`Sudoku::from_str_with`] and displaying with a [`Theme`](crate::Theme). Defaults to the digits `1` to `9`.
+///
+/// Only the characters change, so every solving and validation method works the same whatever the symbols.
+///
+/// # Examples
+///
+/// ```
+/// use newdoku::{Sudoku, SymbolSet};
+///
+/// let letters = SymbolSet::new(['A', 'B', 'C', 'D', 'E', 'F', 'G', 'H', 'I']).unwrap();
+/// assert_eq!(letters.symbol(3), 'C');
+/// assert_eq!(letters.value('I'), Some(9));
+/// assert_eq!(letters.value('1'), None);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SymbolSet {
+    symbols: [char; 9],
+}
+
+impl SymbolSet {
+    /// The digits `1` to `9`, as read by [`Sudoku::from_str`].
+    pub const DIGITS: Self = Self {
+        symbols: ['1', '2', '3', '4', '5', '6', '7', '8', '9'],
+    };
+
+    /// Returns the set in which `symbols[n - 1]` stands for `n`, or [`None`] if a symbol is repeated or is not printable ASCII. Whitespace could not be told apart from blanks and line breaks, and other characters may be drawn wider than the single column each cell of a displayed grid takes.
+    pub fn new(symbols: [char; 9]) -> Option<Self> {
+        let distinct = (0..9).all(|i| !symbols[i + 1..].contains(&symbols[i]));
+        (distinct && symbols.iter().all(char::is_ascii_graphic)).then_some(Self { symbols })
+    }
+
+    /// Returns the symbol of `num`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `num` is not within range 1..=9.
+    pub fn symbol(&self, num: u8) -> char {
+        assert!((1..=9).contains(&num), "numbers must be within range 1..=9");
+        self.symbols[num as usize - 1]
+    }
+
+    /// Returns the number `c` stands for, or [`None`] if it is not one of the symbols.
+    pub fn value(&self, c: char) -> Option<u8> {
+        self.symbols
+            .iter()
+            .position(|&symbol| symbol == c)
+            .map(|i| i as u8 + 1)
+    }
+}
+
+impl Default for SymbolSet {
+    fn default() -> Self {
+        Self::DIGITS
+    }
+}
+
+impl Sudoku {
+    /// Returns a [`Sudoku`] from `src` like [`Sudoku::from_str`](core::str::FromStr::from_str), reading each of `symbols` as its number and any other character as a blank. Newlines are ignored, and the first blank character is returned by [`Sudoku::blank`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use newdoku::{Sudoku, SymbolSet};
+    /// use std::str::FromStr;
+    ///
+    /// let letters = SymbolSet::new(['A', 'B', 'C', 'D', 'E', 'F', 'G', 'H', 'I']).unwrap();
+    /// let s = Sudoku::from_str_with(&format!("AB.{}", ".".repeat(78)), &letters).unwrap();
+    /// assert_eq!(s, Sudoku::from_str(&format!("12.{}", ".".repeat(78))).unwrap());
+    /// ```
+    pub fn from_str_with(src: &str, symbols: &SymbolSet) -> Result<Self, ParseError> {
+        let xs: [Option<SudokuNum>; 81] = match src
+            .chars()
+            .filter(|&c| c != '\n')
+            .map(|c| symbols.value(c).map(SudokuNum::Original))
+            .collect::<Vec<Option<SudokuNum>>>()
+            .try_into()
+        {
+            Ok(xs) => xs,
+            Err(xs) => return Err(ParseError::InvalidLength(xs.len())),
+        };
+        let mut sudoku = Self::from_cells(xs);
+        if let Some(blank) = src
+            .chars()
+            .find(|&c| c != '\n' && symbols.value(c).is_none())
+        {
+            sudoku.blank = blank;
+        }
+        Ok(sudoku)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use core::str::FromStr;
+
+    #[test]
+    fn new() {
+        let letters = ['A', 'B', 'C', 'D', 'E', 'F', 'G', 'H', 'I'];
+        assert!(SymbolSet::new(letters).is_some());
+        let mut repeated = letters;
+        repeated[8] = 'A';
+        assert_eq!(SymbolSet::new(repeated), None);
+        let mut space = letters;
+        space[4] = ' ';
+        assert_eq!(SymbolSet::new(space), None);
+        assert_eq!(
+            SymbolSet::new(['一', '二', '三', '四', '五', '六', '七', '八', '九']),
+            None
+        );
+        let mut accented = letters;
+        accented[0] = 'é';
+        assert_eq!(SymbolSet::new(accented), None);
+        assert_eq!(
+            SymbolSet::new(SymbolSet::DIGITS.symbols),
+            Some(SymbolSet::default())
+        );
+    }
+
+    #[test]
+    fn from_str_with() {
+        let src =
+            "xxxxxxx9xx9x7xx21xxx4x9xxxxx1xxx8xxx7xx42xxx5xx8xxxx748x1xxxx4xxxxxxxxxxxx9613xxx";
+        let s = Sudoku::from_str_with(src, &SymbolSet::DIGITS).unwrap();
+        assert_eq!(s, Sudoku::from_str(src).unwrap());
+        assert_eq!(s.blank(), 'x');
+
+        // Digits are blanks when they are not symbols.
+        let letters = SymbolSet::new(['a', 'b', 'c', 'd', 'e', 'f', 'g', 'h', 'i']).unwrap();
+        let s = Sudoku::from_str_with(&src.replace('x', "0"), &letters).unwrap();
+        assert!(s.xs.iter().all(Option::is_none));
+        assert_eq!(s.blank(), '0');
+        assert_eq!(
+            Sudoku::from_str_with("abc", &letters),
+            Err(ParseError::InvalidLength(3))
+        );
+    }
+}
@@ -0,0 +1,600 @@
+//! Transformations which map a sudoku onto an equivalent one.
+//!
+//! These move cell contents only; extra regions registered with [`Sudoku::add_region`] keep their original positions.
+
+use crate::{Sudoku, SudokuNum};
+use alloc::{vec, vec::Vec};
+
+/// Symmetry of the positions of the givens, as returned by [`Sudoku::given_symmetry`].
+///
+/// Any two of the mirror and rotational symmetries imply the third, so exactly one variant describes any grid.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Symmetry {
+    /// No symmetry.
+    None,
+    /// Unchanged when mirrored top to bottom.
+    Horizontal,
+    /// Unchanged when mirrored left to right.
+    Vertical,
+    /// Unchanged when rotated by 180°, but not mirrored.
+    Rotational,
+    /// Unchanged when mirrored in either direction, and therefore also when rotated by 180°.
+    Full,
+}
+
+/// Offset basis of the 128-bit FNV-1a hash used by [`Sudoku::signature`].
+const FNV_OFFSET: u128 = 0x6c62272e07bb014262b821756295c58d;
+/// Prime of the 128-bit FNV-1a hash used by [`Sudoku::signature`].
+const FNV_PRIME: u128 = 0x0000000001000000000000000000013b;
+
+/// Orderings of three bands, stacks or lines.
+const PERMS: [[usize; 3]; 6] = [
+    [0, 1, 2],
+    [0, 2, 1],
+    [1, 0, 2],
+    [1, 2, 0],
+    [2, 0, 1],
+    [2, 1, 0],
+];
+
+/// Summarises each band, where `get(line, pos)` reads position `pos` of line `line`.
+///
+/// A band is described by how many pairs of mini-lines (the three cells of a line within one block) in different blocks share 0, 1, 2 or 3 digits, how many of its mini-lines hold 0, 1, 2 or 3 digits, and the sorted number of digits in each of its lines. None of these depend on the digits used, the order of the lines within the band or the order of the positions within each block, and the bands are sorted so that their order does not matter either.
+fn band_profiles(get: impl Fn(usize, usize) -> Option<u8>) -> Vec<[u8; 11]> {
+    let mut profiles: Vec<[u8; 11]> = (0..3)
+        .map(|band| {
+            let mut minis = [[0u16; 3]; 3];
+            let mut lines = [0u8; 3];
+            for (line, minis) in minis.iter_mut().enumerate() {
+                for pos in 0..9 {
+                    if let Some(num) = get(band * 3 + line, pos) {
+                        minis[pos / 3] |= 1 << (num - 1);
+                        lines[line] += 1;
+                    }
+                }
+            }
+            lines.sort_unstable();
+
+            let mut profile = [0; 11];
+            for a in 0..9 {
+                let mini = minis[a / 3][a % 3];
+                profile[4 + mini.count_ones() as usize] += 1;
+                for b in (0..9).filter(|b| b % 3 > a % 3) {
+                    profile[(mini & minis[b / 3][b % 3]).count_ones() as usize] += 1;
+                }
+            }
+            profile[8..].copy_from_slice(&lines);
+            profile
+        })
+        .collect();
+    profiles.sort_unstable();
+    profiles
+}
+
+/// Returns true if the cells of `target` can be obtained from `source` by taking row `rows[y]` as row `y`, completing the mapping of columns from column `k` onwards and relabelling the digits.
+///
+/// `cols` holds the source column chosen for each target column before `k`, and `maps` the partial relabelling from source to target digits and back, with `0` for blanks and unmapped digits.
+fn match_cols(
+    source: &[u8; 81],
+    target: &[u8; 81],
+    rows: &[usize; 9],
+    cols: &mut [usize; 9],
+    k: usize,
+    maps: [[u8; 10]; 2],
+) -> bool {
+    if k == 9 {
+        return true;
+    }
+    // Each target stack takes its columns from a single unused source stack.
+    let stacks: Vec<usize> = if k % 3 == 0 {
+        (0..3)
+            .filter(|&stack| cols[..k].iter().all(|&col| col / 3 != stack))
+            .collect()
+    } else {
+        vec![cols[k - 1] / 3]
+    };
+
+    for col in stacks
+        .into_iter()
+        .flat_map(|stack| stack * 3..stack * 3 + 3)
+    {
+        if cols[..k].contains(&col) {
+            continue;
+        }
+        let mut maps = maps;
+        let [forward, back] = &mut maps;
+        let consistent = (0..9).all(|y| {
+            let (a, b) = (source[rows[y] * 9 + col], target[y * 9 + k]);
+            match (a, b) {
+                (0, 0) => true,
+                (0, _) | (_, 0) => false,
+                _ if forward[a as usize] == 0 && back[b as usize] == 0 => {
+                    forward[a as usize] = b;
+                    back[b as usize] = a;
+                    true
+                }
+                _ => forward[a as usize] == b && back[b as usize] == a,
+            }
+        });
+        if consistent {
+            cols[k] = col;
+            if match_cols(source, target, rows, cols, k + 1, maps) {
+                return true;
+            }
+        }
+    }
+    false
+}
+
+impl Sudoku {
+    fn swap_rows(&mut self, a: usize, b: usize) {
+        for x in 0..9 {
+            self.xs.swap(a * 9 + x, b * 9 + x);
+        }
+    }
+
+    fn swap_cols(&mut self, a: usize, b: usize) {
+        for y in 0..9 {
+            self.xs.swap(y * 9 + a, y * 9 + b);
+        }
+    }
+
+    /// Returns the strongest symmetry of the positions of the givens of `self`. Edited cells and the numbers themselves are ignored.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use newdoku::{Sudoku, Symmetry};
+    /// use std::str::FromStr;
+    ///
+    /// let s = Sudoku::from_str(&format!("1{}2", ".".repeat(79))).unwrap();
+    /// assert_eq!(s.given_symmetry(), Symmetry::Rotational);
+    /// ```
+    pub fn given_symmetry(&self) -> Symmetry {
+        let given = |x: usize, y: usize| matches!(self.xs[y * 9 + x], Some(SudokuNum::Original(_)));
+        let symmetric = |map: fn(usize, usize) -> (usize, usize)| {
+            (0..81).all(|i| {
+                let (x, y) = map(i % 9, i / 9);
+                given(i % 9, i / 9) == given(x, y)
+            })
+        };
+
+        let horizontal = symmetric(|x, y| (x, 8 - y));
+        let vertical = symmetric(|x, y| (8 - x, y));
+        match (horizontal, vertical) {
+            (true, true) => Symmetry::Full,
+            (true, false) => Symmetry::Horizontal,
+            (false, true) => Symmetry::Vertical,
+            (false, false) if symmetric(|x, y| (8 - x, 8 - y)) => Symmetry::Rotational,
+            _ => Symmetry::None,
+        }
+    }
+
+    /// Returns a fingerprint of the filled cells of `self` for indexing puzzle collections.
+    ///
+    /// The signature is unchanged by relabelling the digits, transposing, swapping bands or stacks, and swapping rows within a band or columns within a stack, and therefore by every combination of these, including rotations and reflections. Grids which are equivalent under these transforms always have the same signature. The converse does not hold: the signature is a hash of invariants rather than of a canonical form, so distinct classes may collide and a match should be confirmed with a full comparison.
+    ///
+    /// Givens and edited cells are treated alike, and variant constraints are ignored, except that the regions of a jigsaw sudoku are counted in place of the blocks.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use newdoku::Sudoku;
+    /// use std::str::FromStr;
+    ///
+    /// let a = Sudoku::from_str(&format!("12{}", ".".repeat(79))).unwrap();
+    /// let mut b = Sudoku::from_str(&format!("{}21", ".".repeat(79))).unwrap();
+    /// assert_eq!(a.signature(), b.signature());
+    /// b.swap_rows_in_band(2, 2, 0);
+    /// assert_eq!(a.signature(), b.signature());
+    /// ```
+    pub fn signature(&self) -> u128 {
+        let value = |x: usize, y: usize| self.xs[y * 9 + x].map(SudokuNum::value);
+        let rows = band_profiles(|line, pos| value(pos, line));
+        let cols = band_profiles(value);
+        let (first, second) = if rows <= cols {
+            (rows, cols)
+        } else {
+            (cols, rows)
+        };
+
+        let mut blocks = [0u8; 9];
+        for i in (0..81).filter(|&i| self.xs[i].is_some()) {
+            blocks[self.block_of(i)] += 1;
+        }
+        let blocks = PERMS
+            .iter()
+            .flat_map(|bands| PERMS.iter().map(move |stacks| (bands, stacks)))
+            .flat_map(|(bands, stacks)| {
+                [false, true].map(|transpose| {
+                    let mut counts = [0; 9];
+                    for (i, count) in counts.iter_mut().enumerate() {
+                        let (band, stack) = (bands[i / 3], stacks[i % 3]);
+                        *count = if transpose {
+                            blocks[stack * 3 + band]
+                        } else {
+                            blocks[band * 3 + stack]
+                        };
+                    }
+                    counts
+                })
+            })
+            .min()
+            .expect("there are orderings of the blocks");
+
+        let mut digits = [0u8; 9];
+        for num in self.xs.iter().flatten() {
+            digits[num.value() as usize - 1] += 1;
+        }
+        digits.sort_unstable();
+
+        first
+            .iter()
+            .chain(second.iter())
+            .flatten()
+            .chain(blocks.iter())
+            .chain(digits.iter())
+            .fold(FNV_OFFSET, |hash, &byte| {
+                (hash ^ byte as u128).wrapping_mul(FNV_PRIME)
+            })
+    }
+
+    /// Returns true if `other` can be obtained from `self` by relabelling the digits, transposing, swapping bands or stacks, and swapping rows within a band or columns within a stack, in any combination. These include every rotation and reflection of the grid.
+    ///
+    /// Unlike [`Sudoku::signature`] this never reports a false match. Givens and edited cells are treated alike, and variant constraints are ignored.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use newdoku::Sudoku;
+    /// use std::str::FromStr;
+    ///
+    /// let a = Sudoku::from_str(&format!("12{}", ".".repeat(79))).unwrap();
+    /// let b = Sudoku::from_str(&format!("{}3{}7", ".".repeat(71), ".".repeat(8))).unwrap();
+    /// assert!(a.is_equivalent_to(&b));
+    /// let c = Sudoku::from_str(&format!("1..2{}", ".".repeat(77))).unwrap();
+    /// assert!(!a.is_equivalent_to(&c));
+    /// ```
+    pub fn is_equivalent_to(&self, other: &Self) -> bool {
+        self.signature() == other.signature() && self.maps_onto(other)
+    }
+
+    /// Returns `grids` without the puzzles which are equivalent to an earlier one, as decided by [`Sudoku::is_equivalent_to`]. The first puzzle of each class is kept, and the order is otherwise unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use newdoku::Sudoku;
+    /// use std::str::FromStr;
+    ///
+    /// let a = Sudoku::from_str(&format!("12{}", ".".repeat(79))).unwrap();
+    /// let b = Sudoku::from_str(&format!("{}21", ".".repeat(79))).unwrap();
+    /// let c = Sudoku::from_str(&format!("1..2{}", ".".repeat(77))).unwrap();
+    /// let unique = Sudoku::dedup(vec![a.clone(), b, c.clone()]);
+    /// assert_eq!(unique, vec![a, c]);
+    /// ```
+    pub fn dedup(grids: impl IntoIterator<Item = Self>) -> Vec<Self> {
+        let mut unique: Vec<(u128, Self)> = Vec::new();
+        for grid in grids {
+            let signature = grid.signature();
+            if !unique
+                .iter()
+                .any(|(other, kept)| *other == signature && kept.maps_onto(&grid))
+            {
+                unique.push((signature, grid));
+            }
+        }
+        unique.into_iter().map(|(_, grid)| grid).collect()
+    }
+
+    /// Searches every transform and relabelling for one which maps `self` onto `other`.
+    fn maps_onto(&self, other: &Self) -> bool {
+        let cells = |sudoku: &Sudoku| sudoku.xs.map(|x| x.map_or(0, SudokuNum::value));
+        let target = cells(other);
+
+        [false, true].into_iter().any(|transpose| {
+            let mut source = self.clone();
+            if transpose {
+                source.transpose();
+            }
+            let source = cells(&source);
+
+            PERMS.iter().any(|bands| {
+                PERMS.iter().any(|a| {
+                    PERMS.iter().any(|b| {
+                        PERMS.iter().any(|c| {
+                            let within = [a, b, c];
+                            let rows =
+                                core::array::from_fn(|y| bands[y / 3] * 3 + within[y / 3][y % 3]);
+                            match_cols(&source, &target, &rows, &mut [0; 9], 0, [[0; 10]; 2])
+                        })
+                    })
+                })
+            })
+        })
+    }
+
+    /// Swaps bands `a` and `b` (rows of blocks, 0-indexed from the top).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `a` or `b` is not within range 0..3.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use newdoku::Sudoku;
+    /// use std::str::FromStr;
+    ///
+    /// let mut s = Sudoku::from_str(&format!("1{}", ".".repeat(80))).unwrap();
+    /// s.swap_bands(0, 2);
+    /// assert_eq!(s.to_line(), format!("{}1{}", ".".repeat(54), ".".repeat(26)));
+    /// ```
+    pub fn swap_bands(&mut self, a: usize, b: usize) {
+        assert!(a < 3 && b < 3, "band must be within range 0..3");
+        for i in 0..3 {
+            self.swap_rows(a * 3 + i, b * 3 + i);
+        }
+    }
+
+    /// Swaps stacks `a` and `b` (columns of blocks, 0-indexed from the left).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `a` or `b` is not within range 0..3.
+    pub fn swap_stacks(&mut self, a: usize, b: usize) {
+        assert!(a < 3 && b < 3, "stack must be within range 0..3");
+        for i in 0..3 {
+            self.swap_cols(a * 3 + i, b * 3 + i);
+        }
+    }
+
+    /// Swaps rows `a` and `b` within `band`, where the rows are indexed relative to the band.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `band`, `a` or `b` is not within range 0..3.
+    pub fn swap_rows_in_band(&mut self, band: usize, a: usize, b: usize) {
+        assert!(band < 3, "band must be within range 0..3");
+        assert!(a < 3 && b < 3, "row must be within range 0..3 of the band");
+        self.swap_rows(band * 3 + a, band * 3 + b);
+    }
+
+    /// Swaps columns `a` and `b` within `stack`, where the columns are indexed relative to the stack.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `stack`, `a` or `b` is not within range 0..3.
+    pub fn swap_cols_in_stack(&mut self, stack: usize, a: usize, b: usize) {
+        assert!(stack < 3, "stack must be within range 0..3");
+        assert!(a < 3 && b < 3, "col must be within range 0..3 of the stack");
+        self.swap_cols(stack * 3 + a, stack * 3 + b);
+    }
+
+    /// Mirrors the arrangement of the blocks in the main diagonal, so that the block in band `b` and stack `s` moves to band `s` and stack `b`.
+    ///
+    /// Each block is mirrored along with its position, since moving the blocks on their own would break up the rows and columns of a valid grid. This makes it the same transform as [`Sudoku::transpose`], described in terms of blocks to go with [`Sudoku::swap_bands`] and [`Sudoku::swap_stacks`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use newdoku::Sudoku;
+    /// use std::str::FromStr;
+    ///
+    /// let mut s = Sudoku::from_str(&format!("...12{}", ".".repeat(76))).unwrap();
+    /// s.transpose_boxes();
+    /// assert_eq!(s.box_values()[3][..4], [Some(1), None, None, Some(2)]);
+    /// ```
+    pub fn transpose_boxes(&mut self) {
+        self.transpose();
+    }
+
+    /// Mirrors `self` in its main diagonal, so that the cell at `(x, y)` moves to `(y, x)`.
+    pub fn transpose(&mut self) {
+        for y in 0..9 {
+            for x in 0..y {
+                self.xs.swap(y * 9 + x, x * 9 + y);
+            }
+        }
+    }
+
+    /// Replaces every digit `n` of `self` with `map[n - 1]`, keeping whether each cell is a given or an edit.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `map` is not a permutation of the digits 1 to 9.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use newdoku::Sudoku;
+    /// use std::str::FromStr;
+    ///
+    /// let mut s = Sudoku::from_str(&format!("12{}", ".".repeat(79))).unwrap();
+    /// s.relabel([2, 1, 3, 4, 5, 6, 7, 8, 9]);
+    /// assert!(s.to_line().starts_with("21."));
+    /// ```
+    pub fn relabel(&mut self, map: [u8; 9]) {
+        let mut sorted = map;
+        sorted.sort_unstable();
+        assert!(
+            sorted == [1, 2, 3, 4, 5, 6, 7, 8, 9],
+            "map must be a permutation of the digits 1 to 9"
+        );
+
+        for x in self.xs.iter_mut().flatten() {
+            *x = match *x {
+                SudokuNum::Original(num) => SudokuNum::Original(map[num as usize - 1]),
+                SudokuNum::Edited(num) => SudokuNum::Edited(map[num as usize - 1]),
+            };
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::str::FromStr;
+    const TEST_SUDOKU: &str =
+        "xxxxxxx9xx9x7xx21xxx4x9xxxxx1xxx8xxx7xx42xxx5xx8xxxx748x1xxxx4xxxxxxxxxxxx9613xxx";
+    const SOLVED_SUDOKU: &str =
+        "157832496396745218284196753415378962763429185928561374831257649672984531549613827";
+
+    #[test]
+    fn swaps_preserve_validity() {
+        let mut s = Sudoku::from_str(SOLVED_SUDOKU).unwrap();
+        s.swap_bands(0, 1);
+        s.swap_stacks(2, 0);
+        s.swap_rows_in_band(1, 0, 2);
+        s.swap_cols_in_stack(2, 1, 2);
+        assert!(s.is_full() && s.is_valid());
+        assert_ne!(s, Sudoku::from_str(SOLVED_SUDOKU).unwrap());
+    }
+
+    #[test]
+    fn swaps_commute_with_solving() {
+        let mut puzzle = Sudoku::from_str(TEST_SUDOKU).unwrap();
+        let mut solved = Sudoku::from_str(SOLVED_SUDOKU).unwrap();
+        for s in [&mut puzzle, &mut solved] {
+            s.swap_bands(0, 2);
+            s.swap_rows_in_band(0, 0, 1);
+        }
+        assert_eq!(
+            crate::solver::solve(&puzzle, &mut Default::default()),
+            Some(solved)
+        );
+    }
+
+    #[test]
+    fn given_symmetry() {
+        let s = |src: &str| Sudoku::from_str(src).unwrap().given_symmetry();
+        assert_eq!(s(&".".repeat(81)), Symmetry::Full);
+        assert_eq!(
+            s(&format!("1{}2{}", ".".repeat(71), ".".repeat(8))),
+            Symmetry::Horizontal
+        );
+        assert_eq!(
+            s(&format!("1.......2{}", ".".repeat(72))),
+            Symmetry::Vertical
+        );
+        assert_eq!(s(TEST_SUDOKU), Symmetry::None);
+
+        let edited = Sudoku::from_str(&".".repeat(81))
+            .unwrap()
+            .try_insert((0, 0), 1)
+            .unwrap();
+        assert_eq!(edited.given_symmetry(), Symmetry::Full);
+    }
+
+    #[test]
+    fn transpose_and_relabel() {
+        let mut s = Sudoku::from_str(SOLVED_SUDOKU).unwrap();
+        s.transpose();
+        assert!(s.is_full() && s.is_valid());
+        assert_eq!(&s.to_line()[..9], "132479865");
+        s.transpose();
+        assert_eq!(s, Sudoku::from_str(SOLVED_SUDOKU).unwrap());
+
+        s.relabel([9, 8, 7, 6, 5, 4, 3, 2, 1]);
+        assert!(s.is_valid());
+        assert_eq!(&s.to_line()[..9], "953278614");
+    }
+
+    #[test]
+    fn transpose_boxes() {
+        let original = Sudoku::from_str(SOLVED_SUDOKU).unwrap();
+        let mut s = original.clone();
+        s.transpose_boxes();
+        assert!(s.is_full() && s.is_valid());
+        for b in 0..9 {
+            let moved = s.box_values()[b % 3 * 3 + b / 3];
+            for (i, &value) in original.box_values()[b].iter().enumerate() {
+                assert_eq!(moved[i % 3 * 3 + i / 3], value);
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn relabel_not_permutation() {
+        Sudoku::from_str(TEST_SUDOKU)
+            .unwrap()
+            .relabel([1, 1, 3, 4, 5, 6, 7, 8, 9]);
+    }
+
+    #[test]
+    fn is_equivalent_to() {
+        let puzzle = Sudoku::from_str(TEST_SUDOKU).unwrap();
+        let mut s = puzzle.clone();
+        s.swap_bands(0, 2);
+        s.swap_cols_in_stack(1, 0, 2);
+        s.transpose();
+        s.swap_stacks(1, 2);
+        s.relabel([4, 2, 7, 9, 1, 3, 5, 8, 6]);
+        assert!(puzzle.is_equivalent_to(&s));
+        assert!(s.is_equivalent_to(&puzzle));
+
+        let solved = Sudoku::from_str(SOLVED_SUDOKU).unwrap();
+        assert!(!puzzle.is_equivalent_to(&solved));
+        let mut moved = s.clone();
+        let (a, b) = (moved.xs.iter().position(Option::is_some).unwrap(), 80);
+        moved.xs.swap(a, b);
+        assert!(!moved.maps_onto(&s));
+    }
+
+    #[test]
+    fn dedup() {
+        let puzzle = Sudoku::from_str(TEST_SUDOKU).unwrap();
+        let mut moved = puzzle.clone();
+        moved.transpose();
+        moved.swap_bands(0, 1);
+        let solved = Sudoku::from_str(SOLVED_SUDOKU).unwrap();
+        let unique = Sudoku::dedup([puzzle.clone(), solved.clone(), moved, puzzle.clone()]);
+        assert_eq!(unique, vec![puzzle, solved]);
+        assert!(Sudoku::dedup([]).is_empty());
+    }
+
+    #[test]
+    fn signature() {
+        for src in [TEST_SUDOKU, SOLVED_SUDOKU] {
+            let s = Sudoku::from_str(src).unwrap();
+            let mut t = s.clone();
+            t.swap_bands(0, 2);
+            t.swap_stacks(1, 2);
+            t.swap_rows_in_band(1, 0, 2);
+            t.swap_cols_in_stack(0, 0, 1);
+            assert_eq!(s.signature(), t.signature());
+
+            let relabelled: String = src
+                .chars()
+                .map(|c| match c.to_digit(10) {
+                    Some(num) => char::from_digit(num % 9 + 1, 10).unwrap(),
+                    None => c,
+                })
+                .collect();
+            let transposed: String = (0..81)
+                .map(|i| src.as_bytes()[i % 9 * 9 + i / 9] as char)
+                .collect();
+            for other in [relabelled, transposed] {
+                assert_eq!(Sudoku::from_str(&other).unwrap().signature(), s.signature());
+            }
+        }
+
+        let signature = |src: &str| Sudoku::from_str(src).unwrap().signature();
+        assert_ne!(signature(TEST_SUDOKU), signature(SOLVED_SUDOKU));
+        assert_ne!(
+            signature(&format!("12{}", ".".repeat(79))),
+            signature(&format!("1{}2{}", ".".repeat(9), ".".repeat(70)))
+        );
+        assert_ne!(
+            signature(&format!("1..2{}", ".".repeat(77))),
+            signature(&format!("1{}2{}", ".".repeat(8), ".".repeat(71)))
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn swap_bands_out_of_range() {
+        Sudoku::from_str(TEST_SUDOKU).unwrap().swap_bands(0, 3);
+    }
+}
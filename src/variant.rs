@@ -0,0 +1,838 @@
+//! Constraints used by sudoku variants, on top of the usual rows, columns and blocks.
+
+use crate::{ConstraintError, InsertError, Sudoku};
+use alloc::{boxed::Box, vec, vec::Vec};
+
+/// Restricts a cell to odd or even digits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Parity {
+    Odd,
+    Even,
+}
+
+impl Parity {
+    /// Returns true if `num` has this parity.
+    pub fn matches(self, num: u8) -> bool {
+        (num % 2 == 1) == (self == Parity::Odd)
+    }
+}
+
+/// Irregular regions replacing the 3x3 blocks, set with [`Sudoku::with_regions`].
+#[derive(Debug, Clone)]
+pub(crate) struct Jigsaw {
+    /// Region of each cell.
+    pub region_of: [u8; 81],
+    /// Cells of each region in ascending order.
+    pub cells: [[usize; 9]; 9],
+}
+
+/// Variant constraints attached to a [`Sudoku`]. Cells are stored as indices `y * 9 + x`.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Variants {
+    /// Extra regions which must contain distinct digits.
+    pub regions: Vec<Vec<usize>>,
+    /// Paths along which digits must strictly increase, starting from the bulb.
+    pub thermos: Vec<Vec<usize>>,
+    /// Cells restricted to odd or even digits.
+    pub parities: Vec<(usize, Parity)>,
+    /// Pairs of cells which must hold equal digits.
+    pub clones: Vec<(usize, usize)>,
+    /// Regions replacing the blocks in jigsaw sudoku. Boxed since most grids have none.
+    pub jigsaw: Option<Box<Jigsaw>>,
+    /// Killer cages, each with the sum its digits must add up to. The cells of each cage are also an extra region.
+    pub cages: Vec<(Vec<usize>, u32)>,
+    /// Whether cells a king's move apart must hold different digits.
+    pub anti_king: bool,
+    /// Whether orthogonally adjacent cells must not hold consecutive digits.
+    pub non_consecutive: bool,
+    /// Digits ruled out of cells while searching in [`Sudoku::solve_excluding`].
+    pub excluded: Vec<(usize, u8)>,
+}
+
+impl Sudoku {
+    /// Checks whether `num` may be placed at index `idx` without breaking a variant constraint other than an extra region.
+    pub(crate) fn check_variants(&self, idx: usize, num: u8) -> Result<(), InsertError> {
+        if self
+            .variants
+            .parities
+            .iter()
+            .any(|&(i, parity)| i == idx && !parity.matches(num))
+        {
+            return Err(InsertError::ParityViolation);
+        }
+
+        for &(a, b) in &self.variants.clones {
+            let other = match idx {
+                _ if idx == a => b,
+                _ if idx == b => a,
+                _ => continue,
+            };
+            if self.xs[other].is_some_and(|value| value.value() != num) {
+                return Err(InsertError::CloneMismatch);
+            }
+        }
+
+        if self.variants.anti_king
+            && neighbours(idx, &KING_MOVES).any(|i| self.xs[i].is_some_and(|v| v.value() == num))
+        {
+            return Err(InsertError::AntiKingViolation);
+        }
+        if self.variants.non_consecutive
+            && neighbours(idx, &KING_MOVES[..4])
+                .any(|i| self.xs[i].is_some_and(|v| v.value().abs_diff(num) == 1))
+        {
+            return Err(InsertError::ConsecutiveViolation);
+        }
+
+        for (cage, sum) in &self.variants.cages {
+            if cage.contains(&idx) && !cage_can_sum(self, cage, idx, num, *sum) {
+                return Err(InsertError::CageViolation);
+            }
+        }
+
+        let num = num as usize;
+        for thermo in &self.variants.thermos {
+            let k = match thermo.iter().position(|&i| i == idx) {
+                Some(k) => k,
+                None => continue,
+            };
+            // Leave room for the strictly smaller digits before and larger digits after.
+            if num <= k || num + (thermo.len() - 1 - k) > 9 {
+                return Err(InsertError::ThermoViolation);
+            }
+            for (j, &i) in thermo.iter().enumerate() {
+                let value = match self.xs[i] {
+                    Some(value) if i != idx => value.value() as usize,
+                    _ => continue,
+                };
+                if (j < k && value + (k - j) > num) || (j > k && num + (j - k) > value) {
+                    return Err(InsertError::ThermoViolation);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Registers `cells: Vec<(x, y)>` as an extra region which must contain distinct digits.
+    ///
+    /// The region is enforced by [`Sudoku::try_insert`] and therefore by the solver. Regions may hold at most 9 cells.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use newdoku::{InsertError, Sudoku};
+    /// use std::str::FromStr;
+    ///
+    /// let mut s = Sudoku::from_str(&".".repeat(81)).unwrap();
+    /// s.add_region(vec![(0, 0), (8, 8)]).unwrap();
+    /// let s = s.try_insert((0, 0), 5).unwrap();
+    /// assert_eq!(s.try_insert((8, 8), 5), Err(InsertError::RegionDuplicate));
+    /// ```
+    pub fn add_region(&mut self, cells: Vec<(usize, usize)>) -> Result<(), ConstraintError> {
+        if cells.iter().any(|&(x, y)| x > 8 || y > 8) {
+            return Err(ConstraintError::InvalidLoc);
+        }
+
+        let mut region: Vec<usize> = cells.into_iter().map(|(x, y)| y * 9 + x).collect();
+        region.sort_unstable();
+        region.dedup();
+        if region.len() > 9 {
+            return Err(ConstraintError::RegionTooLarge);
+        }
+
+        self.variants.regions.push(region);
+        Ok(())
+    }
+
+    /// Returns `self` with both main diagonals registered as regions (X-Sudoku).
+    pub fn with_diagonals(mut self) -> Self {
+        self.add_region((0..9).map(|i| (i, i)).collect())
+            .expect("diagonal is a valid region");
+        self.add_region((0..9).map(|i| (8 - i, i)).collect())
+            .expect("anti-diagonal is a valid region");
+        self
+    }
+
+    /// Returns `self` with the four extra 3x3 windows registered as regions (Hyper Sudoku, also known as Windoku).
+    pub fn with_hyper(mut self) -> Self {
+        for (ox, oy) in [(1, 1), (5, 1), (1, 5), (5, 5)] {
+            self.add_region((0..9).map(|i| (ox + i % 3, oy + i / 3)).collect())
+                .expect("hyper window is a valid region");
+        }
+        self
+    }
+
+    /// Returns `self` with the nine cells at each position within the blocks, such as every top left cell, registered as regions (Disjoint Groups).
+    pub fn with_disjoint_groups(mut self) -> Self {
+        for pos in 0..9 {
+            self.add_region(
+                (0..9)
+                    .map(|block| (block % 3 * 3 + pos % 3, block / 3 * 3 + pos / 3))
+                    .collect(),
+            )
+            .expect("disjoint group is a valid region");
+        }
+        self
+    }
+
+    /// Returns `self` with each of `thermos` registered as a thermometer: an ordered path `(x, y)` of orthogonally or diagonally adjacent cells, starting at the bulb, along which digits must strictly increase.
+    ///
+    /// Thermometers are enforced by [`Sudoku::try_insert`] and therefore by the solver.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use newdoku::{InsertError, Sudoku};
+    /// use std::str::FromStr;
+    ///
+    /// let s = Sudoku::from_str(&".".repeat(81))
+    ///     .unwrap()
+    ///     .with_thermos(vec![vec![(0, 0), (1, 1), (2, 1)]])
+    ///     .unwrap();
+    /// let s = s.try_insert((1, 1), 5).unwrap();
+    /// assert_eq!(s.try_insert((0, 0), 6), Err(InsertError::ThermoViolation));
+    /// assert!(s.try_insert((0, 0), 4).is_ok());
+    /// ```
+    pub fn with_thermos(
+        mut self,
+        thermos: Vec<Vec<(usize, usize)>>,
+    ) -> Result<Self, ConstraintError> {
+        for thermo in thermos {
+            if thermo.iter().any(|&(x, y)| x > 8 || y > 8) {
+                return Err(ConstraintError::InvalidLoc);
+            }
+            if thermo.len() > 9 {
+                return Err(ConstraintError::ThermoTooLong);
+            }
+            let adjacent = thermo.windows(2).all(|pair| {
+                let (a, b) = (pair[0], pair[1]);
+                a != b && a.0.abs_diff(b.0) <= 1 && a.1.abs_diff(b.1) <= 1
+            });
+            let distinct = (1..thermo.len()).all(|i| !thermo[..i].contains(&thermo[i]));
+            if !adjacent || !distinct {
+                return Err(ConstraintError::InvalidPath);
+            }
+
+            self.variants
+                .thermos
+                .push(thermo.into_iter().map(|(x, y)| y * 9 + x).collect());
+        }
+        Ok(self)
+    }
+
+    /// Returns `self` as a jigsaw sudoku, where the 3x3 blocks are replaced by nine irregular regions. `region_of` holds the region of each cell, indexed `y * 9 + x`, and each of the regions `0..9` must have exactly nine cells.
+    ///
+    /// The regions take the place of the blocks wherever the blocks are used, including [`Sudoku::try_insert`], the solver and the human solving techniques, and are reported as [`Unit::Block`](crate::Unit::Block). [`Display`](core::fmt::Display) draws the borders of the regions in place of the blocks, while [`Sudoku::display_candidates`] and [`Sudoku::to_pretty_ansi`] keep the 3x3 blocks. Methods which rearrange the grid, such as [`Sudoku::transpose`], move cells without moving the regions.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use newdoku::{ConstraintError, InsertError, Sudoku};
+    /// use std::str::FromStr;
+    ///
+    /// // Each row of the grid is its own region.
+    /// let region_of: [usize; 81] = std::array::from_fn(|i| i / 9);
+    /// let s = Sudoku::from_str(&".".repeat(81)).unwrap().with_regions(region_of).unwrap();
+    /// let s = s.try_insert((0, 0), 5).unwrap();
+    /// assert!(s.try_insert((1, 1), 5).is_ok());
+    ///
+    /// let empty = Sudoku::from_str(&".".repeat(81)).unwrap();
+    /// assert_eq!(empty.with_regions([0; 81]).err(), Some(ConstraintError::InvalidRegions));
+    /// ```
+    pub fn with_regions(mut self, region_of: [usize; 81]) -> Result<Self, ConstraintError> {
+        let mut cells = [[0; 9]; 9];
+        let mut sizes = [0; 9];
+        for (idx, &region) in region_of.iter().enumerate() {
+            match sizes.get_mut(region) {
+                Some(size) if *size < 9 => {
+                    cells[region][*size] = idx;
+                    *size += 1;
+                }
+                _ => return Err(ConstraintError::InvalidRegions),
+            }
+        }
+
+        self.variants.jigsaw = Some(Box::new(Jigsaw {
+            region_of: region_of.map(|region| region as u8),
+            cells,
+        }));
+        Ok(self)
+    }
+
+    /// Returns `self` with the two cells `(x, y)` of each of `pairs` registered as clones, which must hold equal digits.
+    ///
+    /// Clones are enforced by [`Sudoku::try_insert`] and therefore by the solver: once one cell of a pair is filled, the other is left with that digit as its only candidate. The cells of a pair must be distinct and must not share a row, column, block or extra region.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use newdoku::{InsertError, Sudoku};
+    /// use std::str::FromStr;
+    ///
+    /// let s = Sudoku::from_str(&".".repeat(81))
+    ///     .unwrap()
+    ///     .with_clones(vec![((0, 0), (4, 4))])
+    ///     .unwrap();
+    /// let s = s.try_insert((0, 0), 7).unwrap();
+    /// assert_eq!(s.candidates(4, 4), vec![7]);
+    /// assert_eq!(s.try_insert((4, 4), 3), Err(InsertError::CloneMismatch));
+    /// ```
+    pub fn with_clones(
+        mut self,
+        pairs: Vec<((usize, usize), (usize, usize))>,
+    ) -> Result<Self, ConstraintError> {
+        for (a, b) in pairs {
+            if a.0 > 8 || a.1 > 8 || b.0 > 8 || b.1 > 8 {
+                return Err(ConstraintError::InvalidLoc);
+            }
+            if a == b || self.peers(a.0, a.1).contains(&b) {
+                return Err(ConstraintError::ClonesArePeers);
+            }
+            self.variants.clones.push((a.1 * 9 + a.0, b.1 * 9 + b.0));
+        }
+        Ok(self)
+    }
+
+    /// Returns `self` with each of `cages` registered as a killer cage: a connected group of cells `(x, y)` holding distinct digits which add up to the sum given with it. Cells are connected through their edges, and a cage holds at most 9 cells.
+    ///
+    /// Cages are enforced by [`Sudoku::try_insert`] and therefore by the solver, and each is also registered as an extra region like [`Sudoku::add_region`]. Returns [`ConstraintError::InvalidCage`] if a cage is not connected or no distinct digits add up to its sum.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use newdoku::{InsertError, Sudoku};
+    /// use std::str::FromStr;
+    ///
+    /// let s = Sudoku::from_str(&".".repeat(81))
+    ///     .unwrap()
+    ///     .with_cages(vec![(vec![(0, 0), (1, 0)], 4)])
+    ///     .unwrap();
+    /// assert_eq!(s.candidates(0, 0), vec![1, 3]);
+    /// let s = s.try_insert((0, 0), 3).unwrap();
+    /// assert_eq!(s.try_insert((1, 0), 2), Err(InsertError::CageViolation));
+    /// ```
+    pub fn with_cages(
+        mut self,
+        cages: Vec<(Vec<(usize, usize)>, u32)>,
+    ) -> Result<Self, ConstraintError> {
+        for (cells, sum) in cages {
+            if cells.iter().any(|&(x, y)| x > 8 || y > 8) {
+                return Err(ConstraintError::InvalidLoc);
+            }
+            let mut cage: Vec<usize> = cells.into_iter().map(|(x, y)| y * 9 + x).collect();
+            cage.sort_unstable();
+            cage.dedup();
+            // The smallest and largest sums of distinct digits filling the cage.
+            let (min, max) = digit_sums(cage.len());
+            if cage.is_empty()
+                || cage.len() > 9
+                || !(min..=max).contains(&sum)
+                || !is_connected(&cage)
+            {
+                return Err(ConstraintError::InvalidCage);
+            }
+
+            self.variants.regions.push(cage.clone());
+            self.variants.cages.push((cage, sum));
+        }
+        Ok(self)
+    }
+
+    /// Returns `self` as a killer sudoku read from the common text format, where `layout` marks the cells of each cage with the same letter and `sums` gives the sum of the cage marked with each letter. The cages are registered with [`Sudoku::with_cages`].
+    ///
+    /// Whitespace in `layout` is ignored, and a `.` marks a cell outside every cage. Returns [`ConstraintError::InvalidLayout`] if `layout` does not have 81 cells and [`ConstraintError::MissingCageSum`] if a letter of `layout` has no sum. Sums of letters which do not appear are ignored.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use newdoku::{ConstraintError, Sudoku};
+    /// use std::str::FromStr;
+    ///
+    /// let layout = format!("AAB{}\n{}", ".".repeat(6), ".".repeat(72));
+    /// let s = Sudoku::from_str(&".".repeat(81))
+    ///     .unwrap()
+    ///     .with_killer_from_str(&layout, &[('A', 3), ('B', 9)])
+    ///     .unwrap();
+    /// assert_eq!(s.candidates(0, 0), vec![1, 2]);
+    /// assert_eq!(s.candidates(2, 0), vec![9]);
+    ///
+    /// let missing = Sudoku::from_str(&".".repeat(81)).unwrap().with_killer_from_str(&layout, &[('A', 3)]);
+    /// assert_eq!(missing.err(), Some(ConstraintError::MissingCageSum('B')));
+    /// ```
+    pub fn with_killer_from_str(
+        self,
+        layout: &str,
+        sums: &[(char, u32)],
+    ) -> Result<Self, ConstraintError> {
+        let cells: Vec<char> = layout.chars().filter(|c| !c.is_whitespace()).collect();
+        if cells.len() != 81 {
+            return Err(ConstraintError::InvalidLayout);
+        }
+
+        let mut letters: Vec<char> = Vec::new();
+        for &c in cells.iter().filter(|&&c| c != '.') {
+            if !letters.contains(&c) {
+                letters.push(c);
+            }
+        }
+        let cages = letters
+            .into_iter()
+            .map(|letter| {
+                let &(_, sum) = sums
+                    .iter()
+                    .find(|&&(c, _)| c == letter)
+                    .ok_or(ConstraintError::MissingCageSum(letter))?;
+                let cage = (0..81)
+                    .filter(|&idx| cells[idx] == letter)
+                    .map(|idx| (idx % 9, idx / 9))
+                    .collect();
+                Ok((cage, sum))
+            })
+            .collect::<Result<Vec<_>, ConstraintError>>()?;
+        self.with_cages(cages)
+    }
+
+    /// Returns `self` with the anti-king constraint, where cells a king's move apart, including diagonally, must hold different digits.
+    ///
+    /// The constraint is enforced by [`Sudoku::try_insert`] and therefore by the solver.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use newdoku::{InsertError, Sudoku};
+    /// use std::str::FromStr;
+    ///
+    /// let s = Sudoku::from_str(&".".repeat(81)).unwrap().with_anti_king();
+    /// let s = s.try_insert((2, 2), 5).unwrap();
+    /// assert_eq!(s.try_insert((3, 3), 5), Err(InsertError::AntiKingViolation));
+    /// assert!(s.try_insert((4, 3), 5).is_ok());
+    /// ```
+    pub fn with_anti_king(mut self) -> Self {
+        self.variants.anti_king = true;
+        self
+    }
+
+    /// Returns `self` with the non-consecutive constraint, where orthogonally adjacent cells must not hold digits which differ by one.
+    ///
+    /// The constraint is enforced by [`Sudoku::try_insert`] and therefore by the solver.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use newdoku::{InsertError, Sudoku};
+    /// use std::str::FromStr;
+    ///
+    /// let s = Sudoku::from_str(&".".repeat(81)).unwrap().with_non_consecutive();
+    /// let s = s.try_insert((0, 0), 5).unwrap();
+    /// assert_eq!(s.candidates(1, 0), vec![1, 2, 3, 7, 8, 9]);
+    /// assert_eq!(s.try_insert((0, 1), 6), Err(InsertError::ConsecutiveViolation));
+    /// assert!(s.try_insert((1, 1), 6).is_ok());
+    /// ```
+    pub fn with_non_consecutive(mut self) -> Self {
+        self.variants.non_consecutive = true;
+        self
+    }
+
+    /// Returns `self` with each cell `(x, y)` of `constraints` restricted to digits of the given [`Parity`].
+    ///
+    /// Parity constraints are enforced by [`Sudoku::try_insert`] and therefore by the solver.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use newdoku::{InsertError, Parity, Sudoku};
+    /// use std::str::FromStr;
+    ///
+    /// let s = Sudoku::from_str(&".".repeat(81))
+    ///     .unwrap()
+    ///     .with_parity(vec![((0, 0), Parity::Even)])
+    ///     .unwrap();
+    /// assert_eq!(s.candidates(0, 0), vec![2, 4, 6, 8]);
+    /// assert_eq!(s.try_insert((0, 0), 3), Err(InsertError::ParityViolation));
+    /// ```
+    pub fn with_parity(
+        mut self,
+        constraints: Vec<((usize, usize), Parity)>,
+    ) -> Result<Self, ConstraintError> {
+        if constraints.iter().any(|&((x, y), _)| x > 8 || y > 8) {
+            return Err(ConstraintError::InvalidLoc);
+        }
+
+        self.variants.parities.extend(
+            constraints
+                .into_iter()
+                .map(|((x, y), parity)| (y * 9 + x, parity)),
+        );
+        Ok(self)
+    }
+}
+
+/// Offsets `(dx, dy)` of the cells a king's move away, with the orthogonal ones first.
+const KING_MOVES: [(isize, isize); 8] = [
+    (0, -1),
+    (-1, 0),
+    (1, 0),
+    (0, 1),
+    (-1, -1),
+    (1, -1),
+    (-1, 1),
+    (1, 1),
+];
+
+/// Returns the indices of the cells at each of `offsets` from `idx` which lie within the grid, so that cells on the edge have fewer neighbours.
+fn neighbours(idx: usize, offsets: &[(isize, isize)]) -> impl Iterator<Item = usize> + '_ {
+    let (x, y) = ((idx % 9) as isize, (idx / 9) as isize);
+    offsets.iter().filter_map(move |&(dx, dy)| {
+        let (x, y) = (x + dx, y + dy);
+        ((0..9).contains(&x) && (0..9).contains(&y)).then(|| (y * 9 + x) as usize)
+    })
+}
+
+/// Returns the smallest and largest sums of `len` distinct digits.
+fn digit_sums(len: usize) -> (u32, u32) {
+    let len = len.min(9) as u32;
+    (len * (len + 1) / 2, len * (19 - len) / 2)
+}
+
+/// Returns true if every cell of `cage` can be reached from the first through edges between cells of `cage`.
+fn is_connected(cage: &[usize]) -> bool {
+    let mut reached = vec![cage[0]];
+    let mut i = 0;
+    while let Some(&idx) = reached.get(i) {
+        for &other in cage {
+            let adjacent = (idx % 9).abs_diff(other % 9) + (idx / 9).abs_diff(other / 9) == 1;
+            if adjacent && !reached.contains(&other) {
+                reached.push(other);
+            }
+        }
+        i += 1;
+    }
+    reached.len() == cage.len()
+}
+
+/// Returns true if `num` at `idx` leaves room for the other cells of `cage` to be filled with distinct digits adding up to `sum`.
+fn cage_can_sum(sudoku: &Sudoku, cage: &[usize], idx: usize, num: u8, sum: u32) -> bool {
+    let mut used = 1u16 << (num - 1);
+    let mut total = num as u32;
+    let mut empty = 0;
+    for &i in cage.iter().filter(|&&i| i != idx) {
+        match sudoku.xs[i] {
+            Some(value) => {
+                used |= 1 << (value.value() - 1);
+                total += value.value() as u32;
+            }
+            None => empty += 1,
+        }
+    }
+    let free: Vec<u32> = (1..10).filter(|d| used & 1 << (d - 1) == 0).collect();
+    sum >= total && can_make(&free, empty, sum - total)
+}
+
+/// Returns true if `count` distinct digits of `free` add up to `target`.
+fn can_make(free: &[u32], count: usize, target: u32) -> bool {
+    match free.split_first() {
+        _ if count == 0 => target == 0,
+        Some((&digit, rest)) => {
+            (digit <= target && can_make(rest, count - 1, target - digit))
+                || can_make(rest, count, target)
+        }
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::str::FromStr;
+
+    fn empty() -> Sudoku {
+        Sudoku::from_str(&".".repeat(81)).unwrap()
+    }
+
+    #[test]
+    fn thermo_invalid() {
+        assert_eq!(
+            empty().with_thermos(vec![vec![(0, 0), (2, 0)]]).err(),
+            Some(ConstraintError::InvalidPath)
+        );
+        assert_eq!(
+            empty()
+                .with_thermos(vec![vec![(0, 0), (1, 0), (0, 0)]])
+                .err(),
+            Some(ConstraintError::InvalidPath)
+        );
+        assert_eq!(
+            empty()
+                .with_thermos(vec![(0..10).map(|i| (i % 9, i / 9)).collect()])
+                .err(),
+            Some(ConstraintError::ThermoTooLong)
+        );
+        assert_eq!(
+            empty().with_thermos(vec![vec![(8, 0), (9, 0)]]).err(),
+            Some(ConstraintError::InvalidLoc)
+        );
+    }
+
+    #[test]
+    fn thermo_bounds() {
+        let s = empty()
+            .with_thermos(vec![vec![(0, 0), (1, 0), (2, 0)]])
+            .unwrap();
+        assert_eq!(s.candidates(0, 0), (1..8).collect::<Vec<u8>>());
+        assert_eq!(s.candidates(2, 0), (3..10).collect::<Vec<u8>>());
+        let s = s.try_insert((2, 0), 4).unwrap();
+        assert_eq!(s.candidates(1, 0), vec![2, 3]);
+
+        let mut s = s;
+        s.xs[0] = Some(crate::SudokuNum::Original(4));
+        assert!(!s.is_valid());
+    }
+
+    #[test]
+    fn disjoint_groups() {
+        let s = empty().with_disjoint_groups();
+        assert_eq!(s.variants.regions.len(), 9);
+        assert!(s.variants.regions.iter().all(|region| region.len() == 9));
+        let s = s.try_insert((0, 0), 5).unwrap();
+        assert_eq!(s.try_insert((3, 3), 5), Err(InsertError::RegionDuplicate));
+        assert!(s.try_insert((4, 3), 5).is_ok());
+
+        let solution = crate::solver::solve(&s, &mut Default::default()).unwrap();
+        for pos in 0..9 {
+            let mut seen: Vec<char> = (0..9)
+                .map(|block| {
+                    solution.to_line().as_bytes()
+                        [(block / 3 * 3 + pos / 3) * 9 + block % 3 * 3 + pos % 3]
+                        as char
+                })
+                .collect();
+            seen.sort_unstable();
+            seen.dedup();
+            assert_eq!(seen.len(), 9);
+        }
+    }
+
+    #[test]
+    fn parity() {
+        assert_eq!(
+            empty().with_parity(vec![((9, 0), Parity::Odd)]).err(),
+            Some(ConstraintError::InvalidLoc)
+        );
+
+        let s = empty()
+            .with_parity((0..9).map(|x| ((x, 0), Parity::Odd)).collect())
+            .unwrap();
+        assert_eq!(s.candidates(4, 0), vec![1, 3, 5, 7, 9]);
+        assert!(crate::solver::solve(&s, &mut Default::default()).is_none());
+
+        let s = empty()
+            .with_parity((0..4).map(|x| ((x, 0), Parity::Even)).collect())
+            .unwrap();
+        let solution = crate::solver::solve(&s, &mut Default::default()).unwrap();
+        assert!(solution.to_line()[..4]
+            .bytes()
+            .all(|b| Parity::Even.matches(b - b'0')));
+
+        let mut s = s;
+        s.xs[0] = Some(crate::SudokuNum::Original(3));
+        assert!(!s.is_valid());
+    }
+
+    #[test]
+    fn clones() {
+        assert_eq!(
+            empty().with_clones(vec![((0, 0), (0, 9))]).err(),
+            Some(ConstraintError::InvalidLoc)
+        );
+        for pair in [
+            ((0, 0), (0, 0)),
+            ((0, 0), (8, 0)),
+            ((0, 0), (0, 8)),
+            ((0, 0), (2, 2)),
+        ] {
+            assert_eq!(
+                empty().with_clones(vec![pair]).err(),
+                Some(ConstraintError::ClonesArePeers)
+            );
+        }
+        // Cells on the same diagonal of an X-Sudoku are peers too.
+        let diagonal = empty().with_diagonals();
+        assert!(diagonal.clone().with_clones(vec![((3, 0), (4, 4))]).is_ok());
+        assert_eq!(
+            diagonal.with_clones(vec![((0, 0), (4, 4))]).err(),
+            Some(ConstraintError::ClonesArePeers)
+        );
+
+        let pairs: Vec<_> = (0..9).map(|x| ((x, 0), ((x + 3) % 9, 1))).collect();
+        let s = empty().with_clones(pairs).unwrap();
+        let solution = crate::solver::solve(&s, &mut Default::default()).unwrap();
+        assert!(solution.is_valid());
+        let line = solution.to_line();
+        for x in 0..9 {
+            assert_eq!(line.as_bytes()[x], line.as_bytes()[9 + (x + 3) % 9]);
+        }
+
+        let mut s = s;
+        s.xs[0] = Some(crate::SudokuNum::Original(1));
+        s.xs[12] = Some(crate::SudokuNum::Original(2));
+        assert!(!s.is_valid());
+    }
+
+    #[test]
+    fn jigsaw() {
+        let mut region_of = [0; 81];
+        for (idx, region) in region_of.iter_mut().enumerate() {
+            *region = idx / 27 * 3 + idx % 9 / 3;
+        }
+        // Swap a cell between the first two blocks, so `(3, 1)` joins the first and `(2, 0)` the second.
+        region_of.swap(2, 12);
+        let s = empty().with_regions(region_of).unwrap();
+        assert_eq!(s.peers(3, 1).len(), 21);
+        assert!(s.peers(3, 1).contains(&(0, 2)));
+        assert!(!s.peers(3, 1).contains(&(4, 2)));
+
+        let s = s.try_insert((3, 1), 7).unwrap();
+        assert_eq!(s.try_insert((1, 2), 7), Err(InsertError::BlockDuplicate));
+        assert!(s.try_insert((4, 2), 7).is_ok());
+
+        // The swapped cells hold the same digit in the solution, so it still solves the puzzle.
+        let puzzle = Sudoku::from_str(
+            "xxxxxxx9xx9x7xx21xxx4x9xxxxx1xxx8xxx7xx42xxx5xx8xxxx748x1xxxx4xxxxxxxxxxxx9613xxx",
+        )
+        .unwrap()
+        .with_regions(region_of)
+        .unwrap();
+        let solution = crate::solver::solve(&puzzle, &mut Default::default()).unwrap();
+        assert!(solution.is_valid() && solution.is_full());
+        assert_eq!(
+            solution.to_line(),
+            "157832496396745218284196753415378962763429185928561374831257649672984531549613827"
+        );
+        assert_eq!(solution.box_values()[0][5], Some(7));
+        assert_eq!(solution.box_values()[1][0], Some(7));
+
+        let mut invalid = region_of;
+        invalid[0] = 9;
+        assert_eq!(
+            empty().with_regions(invalid).err(),
+            Some(ConstraintError::InvalidRegions)
+        );
+        invalid[0] = 1;
+        assert_eq!(
+            empty().with_regions(invalid).err(),
+            Some(ConstraintError::InvalidRegions)
+        );
+    }
+
+    #[test]
+    fn solve_thermo() {
+        let s = empty()
+            .with_thermos(vec![(0..9).map(|x| (x, 4)).collect()])
+            .unwrap();
+        let solution = crate::solver::solve(&s, &mut Default::default()).unwrap();
+        assert!(solution.is_valid());
+        assert_eq!(&solution.to_line()[36..45], "123456789");
+    }
+
+    #[test]
+    fn killer() {
+        const SOLVED: &str =
+            "157832496396745218284196753415378962763429185928561374831257649672984531549613827";
+        // Each row is split into four dominoes and a single cell, with sums taken from SOLVED.
+        let letters: Vec<char> = ('A'..='Z').chain('a'..='s').collect();
+        let cage = |idx: usize| idx / 9 * 5 + (idx % 9) / 2;
+        let layout: String = (0..81).map(|idx| letters[cage(idx)]).collect();
+        let mut sums = vec![(' ', 0); 45];
+        for (idx, c) in SOLVED.chars().enumerate() {
+            sums[cage(idx)].0 = letters[cage(idx)];
+            sums[cage(idx)].1 += c.to_digit(10).unwrap();
+        }
+
+        let givens =
+            "xxxxxxx9xx9x7xx21xxx4x9xxxxx1xxx8xxx7xx42xxx5xx8xxxx748x1xxxx4xxxxxxxxxxxx9613xxx";
+        let s = Sudoku::from_str(givens)
+            .unwrap()
+            .with_killer_from_str(&layout, &sums)
+            .unwrap();
+        let solution = crate::solver::solve(&s, &mut Default::default()).unwrap();
+        assert_eq!(solution.to_line(), SOLVED);
+        assert!(solution.is_valid());
+
+        sums[0].1 += 1;
+        let s = Sudoku::from_str(givens)
+            .unwrap()
+            .with_killer_from_str(&layout, &sums)
+            .unwrap();
+        assert_eq!(crate::solver::solve(&s, &mut Default::default()), None);
+    }
+
+    #[test]
+    fn killer_invalid() {
+        let rest = ".".repeat(79);
+        assert_eq!(
+            empty()
+                .with_killer_from_str(&format!("A.A{}", &rest[1..]), &[('A', 3)])
+                .err(),
+            Some(ConstraintError::InvalidCage)
+        );
+        assert_eq!(
+            empty()
+                .with_killer_from_str(&format!("AA{}", rest), &[('A', 18)])
+                .err(),
+            Some(ConstraintError::InvalidCage)
+        );
+        assert_eq!(
+            empty()
+                .with_killer_from_str(&format!("AA{}", rest), &[('B', 3)])
+                .err(),
+            Some(ConstraintError::MissingCageSum('A'))
+        );
+        assert_eq!(
+            empty().with_killer_from_str(&rest, &[]).err(),
+            Some(ConstraintError::InvalidLayout)
+        );
+        assert_eq!(
+            empty().with_cages(vec![(vec![(0, 0), (9, 0)], 3)]).err(),
+            Some(ConstraintError::InvalidLoc)
+        );
+        // Cells meeting only at a corner are not connected.
+        assert_eq!(
+            empty().with_cages(vec![(vec![(0, 0), (1, 1)], 3)]).err(),
+            Some(ConstraintError::InvalidCage)
+        );
+        assert!(empty()
+            .with_cages(vec![(vec![(0, 0), (1, 0), (1, 1)], 6)])
+            .is_ok());
+    }
+
+    #[test]
+    fn anti_king() {
+        let s = empty().with_anti_king();
+        let solution = crate::solver::solve(&s, &mut Default::default()).unwrap();
+        assert!(solution.is_valid());
+        for idx in 0..81 {
+            let num = solution.xs[idx].unwrap().value();
+            assert!(neighbours(idx, &KING_MOVES).all(|i| solution.xs[i].unwrap().value() != num));
+        }
+        assert_eq!(neighbours(0, &KING_MOVES).count(), 3);
+        assert_eq!(neighbours(4, &KING_MOVES).count(), 5);
+    }
+
+    #[test]
+    fn non_consecutive() {
+        let s = empty().with_non_consecutive();
+        let solution = crate::solver::solve(&s, &mut Default::default()).unwrap();
+        assert!(solution.is_valid());
+        for idx in 0..81 {
+            let num = solution.xs[idx].unwrap().value();
+            assert!(neighbours(idx, &KING_MOVES[..4]).all(|i| solution.xs[i]
+                .unwrap()
+                .value()
+                .abs_diff(num)
+                != 1));
+        }
+        // Diagonal neighbours may be consecutive.
+        let s = s.try_insert((0, 0), 5).unwrap();
+        assert!(s.try_insert((1, 1), 4).is_ok());
+    }
+}